@@ -1,43 +1,69 @@
-use pathfinding::prelude::dijkstra;
-use pathfinding::{grid::Grid, prelude::Matrix};
+use std::collections::HashMap;
+
+use ::pathfinding::prelude::dijkstra;
 
 wit_bindgen::generate!({
-    path: "../../wit",
+    path: "../../wit/v2",
     world: "pathfinding",
 });
-use crate::exports::guest;
-use crate::host::*;
+use crate::exports::pathfinding::pathfinding::guest;
+use crate::pathfinding::pathfinding::host::*;
+use crate::pathfinding::pathfinding::types::{Grid, Movement, Pos};
 
 struct MyImpl;
 
+// (0, 0) is the bottom left tile, rows go from bottom to top
+fn is_floor(grid: &Grid, pos: Pos) -> bool {
+    grid.cells[(pos.y * grid.width + pos.x) as usize] == 0
+}
+
 impl guest::Guest for MyImpl {
-    fn run(input: Vec<Vec<bool>>, start: (u32, u32), end: (u32, u32)) {
-        let matrix = Matrix::from_vec(
-            input.len(),
-            input[0].len(),
-            input.into_iter().flatten().collect(),
-        )
-        .unwrap();
-
-        let grid: Grid = matrix.into();
-        
-        println!("{grid:?}");
-
-        let result: (Vec<(u32, u32)>, u32) = dijkstra(
-            &start,
-            |n| {
-                grid.neighbours((n.0 as usize, n.1 as usize))
+    // Graph is provided by host, so there is no need to build our own grid
+    fn run(grid: Grid, start: Pos, end: Pos, _movement: Movement) {
+        println!("Map: {}x{}", grid.width, grid.height);
+        assert!(is_floor(&grid, start) && is_floor(&grid, end));
+
+        // Best cost from start so far, `dijkstra` doesn't pass it to successors
+        // It is final once the node is expanded
+        let mut costs = HashMap::from([((start.x, start.y), 0)]);
+
+        // `Pos` is not hashable, so search over plain (x, y) tuples
+        let result: Option<(Vec<(u32, u32)>, u32)> = dijkstra(
+            &(start.x, start.y),
+            |&(x, y)| {
+                expand(Pos { x, y });
+                let cost_so_far = costs[&(x, y)];
+                neighbors(Pos { x, y })
                     .into_iter()
-                    .map(|n| ((n.0 as u32, n.1 as u32), 1))
-                    .collect::<Vec<((u32, u32), u32)>>()
+                    .map(|(pos, cost)| {
+                        let next_cost = cost_so_far + cost;
+                        if costs
+                            .get(&(pos.x, pos.y))
+                            .is_none_or(|best| next_cost < *best)
+                        {
+                            costs.insert((pos.x, pos.y), next_cost);
+                            // Priority is f = g + h, Dijkstra has no heuristic
+                            enqueue(pos, next_cost);
+                        }
+                        ((pos.x, pos.y), cost)
+                    })
+                    .collect::<Vec<_>>()
             },
-            |n| *n == end,
-        )
-        .unwrap();
+            |&(x, y)| x == end.x && y == end.y,
+        );
+
+        // Flag is walled off
+        let Some(result) = result else {
+            println!("No path");
+            no_path();
+            return;
+        };
 
         println!("{result:?}");
+        found(end);
 
-        output(&result.0);
+        let path: Vec<Pos> = result.0.into_iter().map(|(x, y)| Pos { x, y }).collect();
+        output(&path);
     }
 }
 
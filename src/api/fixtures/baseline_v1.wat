@@ -0,0 +1,46 @@
+;; Guest of the original API (`wit/world.wit` before worlds were versioned):
+;; 3-argument `run`, `host` with drawing functions only.
+;; Colors the start tile red, like the first thing a real guest draws
+(component
+  (import "host" (instance $host
+    (type $pos (tuple u32 u32))
+    (export "pos" (type $pos-type (eq $pos)))
+    (type $color (tuple u8 u8 u8))
+    (export "color" (type $color-type (eq $color)))
+    (export "tile" (func (param "pos" $pos-type) (param "color" $color-type)))
+  ))
+
+  (core module $guest
+    (import "host" "tile" (func $tile (param i32 i32 i32 i32 i32)))
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+
+    ;; Bump allocator for the map passed to `run`
+    (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get 2))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get 3)))
+      (local.get $ptr))
+
+    ;; input: ptr, len; start: x, y; end: x, y
+    (func (export "run") (param i32 i32 i32 i32 i32 i32)
+      (call $tile (local.get 2) (local.get 3) (i32.const 255) (i32.const 0) (i32.const 0)))
+  )
+
+  (core func $tile (canon lower (func $host "tile")))
+  (core instance $host-core (export "tile" (func $tile)))
+  (core instance $guest-core (instantiate $guest (with "host" (instance $host-core))))
+
+  (func $run
+    (param "input" (list (list bool)))
+    (param "start" (tuple u32 u32))
+    (param "end" (tuple u32 u32))
+    (canon lift (core func $guest-core "run")
+      (memory $guest-core "memory")
+      (realloc (func $guest-core "cabi_realloc"))))
+  (instance $guest-instance (export "run" (func $run)))
+  (export "guest" (instance $guest-instance))
+)
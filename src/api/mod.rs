@@ -0,0 +1,672 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{log::warn, prelude::Color, reflect::Reflect};
+use wasmtime::{
+    Engine, Store, UpdateDeadline,
+    component::{Component, Linker},
+};
+use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
+
+use crate::{
+    debugger::Breakpoints,
+    layers::{DEFAULT_LAYER, PATH_LAYER},
+    map::{Map, MapPos},
+    movement::Movement,
+    reference,
+};
+
+pub mod v1;
+pub mod v2;
+
+const PACKAGE: &str = "pathfinding:pathfinding";
+
+// Supported WIT worlds, one directory in `wit/` per version
+// v1 - `wit/v1`, nested `list<list<bool>>` map and tuple positions
+// v2 - `wit/v2`, flat grid and `pos` records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+impl ApiVersion {
+    pub const SUPPORTED: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn semver(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => v1::VERSION,
+            ApiVersion::V2 => v2::VERSION,
+        }
+    }
+
+    fn from_semver(version: &str) -> Option<ApiVersion> {
+        Self::SUPPORTED
+            .into_iter()
+            .find(|supported| supported.semver() == version)
+    }
+
+    // Finds the world by guest export, then checks that all imports from
+    // our package belong to the same version
+    pub fn detect(engine: &Engine, component: &Component) -> wasmtime::Result<ApiVersion> {
+        let component_type = component.component_type();
+
+        let Some(guest_version) =
+            component_type
+                .exports(engine)
+                .find_map(|(name, _)| match parse_interface(name) {
+                    Some(("guest", version)) => Some(version.to_owned()),
+                    _ => None,
+                })
+        else {
+            return Err(wasmtime::Error::msg(format!(
+                "Component doesn't export `{PACKAGE}/guest`, is it built against `wit/`?"
+            )));
+        };
+
+        let Some(version) = ApiVersion::from_semver(&guest_version) else {
+            return Err(wasmtime::Error::msg(format!(
+                "Component targets {PACKAGE}@{guest_version}, supported versions: {}",
+                Self::SUPPORTED.map(ApiVersion::semver).join(", ")
+            )));
+        };
+
+        for (name, _) in component_type.imports(engine) {
+            if let Some((interface, import_version)) = parse_interface(name)
+                && import_version != guest_version
+            {
+                return Err(wasmtime::Error::msg(format!(
+                    "Component exports guest@{guest_version} but imports {interface}@{import_version}"
+                )));
+            }
+        }
+
+        Ok(version)
+    }
+
+    // Only the detected version is linked, so each guest sees exactly its own API
+    pub fn add_to_linker(self, linker: &mut Linker<WasmRunner>) -> wasmtime::Result<()> {
+        match self {
+            ApiVersion::V1 => v1::add_to_linker(linker),
+            ApiVersion::V2 => v2::add_to_linker(linker),
+        }
+    }
+}
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.semver())
+    }
+}
+
+// `pathfinding:pathfinding/guest@2.0.0` -> ("guest", "2.0.0")
+// Legacy unversioned `guest` and `host` belong to v1
+fn parse_interface(name: &str) -> Option<(&str, &str)> {
+    if name == v1::GUEST || name == v1::HOST {
+        return Some((name, v1::VERSION));
+    }
+
+    name.strip_prefix(PACKAGE)?
+        .strip_prefix('/')?
+        .split_once('@')
+}
+
+// Component with imports already resolved, instantiated once per run
+pub enum PathfindingPre {
+    V1(v1::PathfindingPre<WasmRunner>),
+    V2(v2::PathfindingPre<WasmRunner>),
+}
+impl PathfindingPre {
+    pub fn new(
+        version: ApiVersion,
+        component: &Component,
+        linker: &Linker<WasmRunner>,
+    ) -> wasmtime::Result<PathfindingPre> {
+        let instance_pre = linker.instantiate_pre(component)?;
+        Ok(match version {
+            ApiVersion::V1 => PathfindingPre::V1(v1::PathfindingPre::new(instance_pre)?),
+            ApiVersion::V2 => PathfindingPre::V2(v2::PathfindingPre::new(instance_pre)?),
+        })
+    }
+
+    pub async fn instantiate(
+        &self,
+        store: &mut Store<WasmRunner>,
+    ) -> wasmtime::Result<Pathfinding> {
+        Ok(match self {
+            PathfindingPre::V1(pre) => Pathfinding::V1(pre.instantiate_async(store).await?),
+            PathfindingPre::V2(pre) => Pathfinding::V2(pre.instantiate_async(store).await?),
+        })
+    }
+}
+
+pub enum Pathfinding {
+    V1(v1::Pathfinding),
+    V2(v2::Pathfinding),
+}
+impl Pathfinding {
+    pub async fn call_run(
+        &self,
+        store: &mut Store<WasmRunner>,
+        input: &RunInput,
+    ) -> wasmtime::Result<()> {
+        match self {
+            Pathfinding::V1(module) => v1::call_run(module, store, input).await,
+            Pathfinding::V2(module) => v2::call_run(module, store, input).await,
+        }
+    }
+}
+
+pub fn rgb((r, g, b): (u8, u8, u8)) -> Color {
+    Color::srgb_u8(r, g, b)
+}
+
+pub fn rgba((r, g, b, a): (u8, u8, u8, u8)) -> Color {
+    Color::srgba_u8(r, g, b, a)
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub enum TimelineAction {
+    Tile {
+        pos: MapPos,
+        color: Color,
+    },
+    Line {
+        start: MapPos,
+        end: MapPos,
+        color: Color,
+    },
+    Arrow {
+        start: MapPos,
+        end: MapPos,
+        color: Color,
+    },
+    // Semantic search events
+    Enqueue {
+        pos: MapPos,
+        priority: u32,
+    },
+    Expand {
+        pos: MapPos,
+    },
+    SetParent {
+        child: MapPos,
+        parent: MapPos,
+    },
+    Found {
+        pos: MapPos,
+    },
+    // Tile labels
+    Label {
+        pos: MapPos,
+        text: String,
+    },
+    Values {
+        pos: MapPos,
+        values: Vec<f32>,
+    },
+    // Shapes, filled ones are stacked in timeline order
+    Rect {
+        min: MapPos,
+        max: MapPos,
+        color: Color,
+        filled: bool,
+    },
+    Circle {
+        center: MapPos,
+        radius: f32,
+        color: Color,
+        filled: bool,
+    },
+    Polyline {
+        points: Vec<MapPos>,
+        color: Color,
+    },
+    // Heatmap, colored by host
+    Scalar {
+        pos: MapPos,
+        value: f32,
+    },
+    ScalarField {
+        values: Vec<f32>,
+    },
+    FlowField {
+        directions: Vec<(i8, i8)>,
+    },
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct TimelineEntry {
+    // Index in `Timeline::layers`
+    pub layer: usize,
+    pub action: TimelineAction,
+}
+
+// Shared with the host, so it can be drawn while the guest is suspended
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    pub entries: Vec<TimelineEntry>,
+    pub layers: Vec<String>,
+}
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline {
+            entries: Vec::new(),
+            layers: vec![DEFAULT_LAYER.to_owned()],
+        }
+    }
+}
+
+impl TimelineAction {
+    // Tiles recolored by this action, for tile breakpoints
+    fn colors_tile(&self, tile: MapPos, width: u32) -> bool {
+        match self {
+            TimelineAction::Tile { pos, .. }
+            | TimelineAction::Enqueue { pos, .. }
+            | TimelineAction::Expand { pos }
+            | TimelineAction::Found { pos }
+            | TimelineAction::Scalar { pos, .. } => *pos == tile,
+            TimelineAction::ScalarField { values } => values
+                .get((tile.y * width + tile.x) as usize)
+                .is_some_and(|value| !value.is_nan()),
+            _ => false,
+        }
+    }
+
+    // Tile the action draws on, `None` for shapes and fields
+    pub fn tile(&self) -> Option<MapPos> {
+        match self {
+            TimelineAction::Tile { pos, .. }
+            | TimelineAction::Enqueue { pos, .. }
+            | TimelineAction::Expand { pos }
+            | TimelineAction::Found { pos }
+            | TimelineAction::Label { pos, .. }
+            | TimelineAction::Values { pos, .. }
+            | TimelineAction::Scalar { pos, .. } => Some(*pos),
+            TimelineAction::SetParent { child, .. } => Some(*child),
+            _ => None,
+        }
+    }
+
+    // Values of whole-map fields, one per tile
+    pub fn field_len(&self) -> Option<usize> {
+        match self {
+            TimelineAction::ScalarField { values } => Some(values.len()),
+            TimelineAction::FlowField { directions } => Some(directions.len()),
+            _ => None,
+        }
+    }
+}
+
+// Shared with the host like `Timeline`, guest yields when a breakpoint is hit
+#[derive(Debug, Clone, Default)]
+pub struct Debugger {
+    pub breakpoints: Breakpoints,
+    // Break after the next host call
+    pub step: bool,
+    // Why the guest is paused, cleared when it continues
+    pub hit: Option<String>,
+}
+
+// Everything the guest was asked to solve, used to validate its output
+#[derive(Debug, Clone, Default)]
+pub struct RunInput {
+    pub map: Map,
+    pub movement: Movement,
+    pub start: MapPos,
+    pub end: MapPos,
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct PathOutput {
+    pub path: Vec<MapPos>,
+    // Total cost if path is valid, reason otherwise
+    pub cost: Result<u32, String>,
+    // Cost of the reference solver, `None` if the flag can't be reached
+    #[reflect(default)]
+    pub optimal: Option<u32>,
+    // Set only by `no-path`, an empty `output` is an invalid path
+    #[reflect(default)]
+    pub no_path: bool,
+}
+impl PathOutput {
+    // Guest reported that the flag can't be reached
+    pub fn is_no_path(&self) -> bool {
+        self.no_path
+    }
+
+    // Valid path, but a cheaper one exists
+    pub fn is_suboptimal(&self) -> bool {
+        matches!((&self.cost, self.optimal), (Ok(cost), Some(optimal)) if *cost > optimal)
+    }
+}
+
+// Tracked from search events
+#[derive(Debug, Clone, Default)]
+pub struct SearchStats {
+    pub open: HashSet<MapPos>,
+    pub max_open: usize,
+    pub expanded: u32,
+}
+
+// Guest yields to the host after `batch` host calls, so its drawing can be shown mid-run
+#[derive(Debug, Clone)]
+pub struct StreamState {
+    // `None` runs the guest to the end
+    pub batch: Option<usize>,
+    pending: usize,
+    yield_requested: bool,
+    // Epoch is bumped to reach the deadline callback at the next guest check
+    engine: Engine,
+}
+
+pub struct WasmRunner {
+    pub wasi_ctx: WasiCtx,              // For WASI
+    pub table: ResourceTable,           // For WASI
+    pub timeline: Arc<Mutex<Timeline>>, // For pathfinding API
+    pub layer: usize,                   // For pathfinding API
+    pub stream: StreamState,            // For pathfinding API
+    pub debugger: Arc<Mutex<Debugger>>, // For debugging
+    pub calls: u32,                     // For debugging
+    pub input: RunInput,                // For validation
+    pub output: Option<PathOutput>,     // For validation
+    pub queries: HashMap<MapPos, u32>,  // For statistics
+    pub search: SearchStats,            // For statistics
+}
+
+impl WasmRunner {
+    pub fn new(
+        engine: &Engine,
+        timeline: Arc<Mutex<Timeline>>,
+        debugger: Arc<Mutex<Debugger>>,
+    ) -> Self {
+        WasmRunner {
+            wasi_ctx: WasiCtxBuilder::new()
+                .inherit_stdin()
+                .inherit_stdout()
+                .build(),
+            table: ResourceTable::new(),
+            timeline,
+            layer: 0,
+            stream: StreamState {
+                batch: None,
+                pending: 0,
+                yield_requested: false,
+                engine: engine.clone(),
+            },
+            debugger,
+            calls: 0,
+            input: RunInput::default(),
+            output: None,
+            queries: HashMap::new(),
+            search: SearchStats::default(),
+        }
+    }
+
+    // Called from the epoch deadline callback
+    pub fn next_deadline(&mut self) -> UpdateDeadline {
+        match std::mem::take(&mut self.stream.yield_requested) {
+            true => UpdateDeadline::Yield(1),
+            false => UpdateDeadline::Continue(1),
+        }
+    }
+
+    // Called from the call hook before every host function, WASI included
+    pub fn host_call(&mut self) {
+        self.calls += 1;
+
+        if let Some(batch) = self.stream.batch {
+            self.stream.pending += 1;
+            if self.stream.pending >= batch {
+                self.stream.pending = 0;
+                self.request_yield();
+            }
+        }
+
+        let mut debugger = self.debugger.lock().unwrap();
+        let reason = if std::mem::take(&mut debugger.step) {
+            format!("Step, host call {}", self.calls)
+        } else if debugger.breakpoints.calls == Some(self.calls) {
+            format!("Host call {}", self.calls)
+        } else {
+            return;
+        };
+        drop(debugger);
+
+        self.break_at(reason);
+    }
+
+    fn break_at(&mut self, reason: String) {
+        self.debugger.lock().unwrap().hit = Some(reason);
+        self.request_yield();
+    }
+
+    fn request_yield(&mut self) {
+        self.stream.yield_requested = true;
+        self.stream.engine.increment_epoch();
+    }
+}
+
+impl WasiView for WasmRunner {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.wasi_ctx,
+            table: &mut self.table,
+        }
+    }
+}
+
+// Host API shared between all versions, versioned `Host` traits only convert types
+impl WasmRunner {
+    // Runner is created for every run, previous runs can't leak into this one
+    pub fn start(&mut self, input: RunInput, batch: Option<usize>) {
+        self.input = input;
+        self.stream.batch = batch;
+
+        let mut debugger = self.debugger.lock().unwrap();
+        debugger.step = false;
+        debugger.hit = None;
+    }
+
+    fn push(&mut self, action: TimelineAction) {
+        let tile = self.debugger.lock().unwrap().breakpoints.tile;
+        if let Some(tile) = tile
+            && action.colors_tile(tile, self.input.map.width())
+        {
+            self.break_at(format!("Tile ({}, {}) colored", tile.x, tile.y));
+        }
+
+        self.timeline.lock().unwrap().entries.push(TimelineEntry {
+            layer: self.layer,
+            action,
+        });
+    }
+
+    fn guest_breakpoint(&mut self, label: &str) {
+        self.break_at(format!("Breakpoint: {label}"));
+    }
+
+    fn set_layer(&mut self, name: &str) {
+        let layers = &mut self.timeline.lock().unwrap().layers;
+        self.layer = match layers.iter().position(|layer| layer == name) {
+            Some(index) => index,
+            None => {
+                layers.push(name.to_owned());
+                layers.len() - 1
+            }
+        };
+    }
+
+    fn query(&mut self, pos: MapPos) {
+        *self.queries.entry(pos).or_default() += 1;
+    }
+
+    fn validate(&self, path: &[MapPos]) -> Result<u32, String> {
+        let RunInput {
+            map,
+            movement,
+            start,
+            end,
+        } = &self.input;
+
+        match (path.first(), path.last()) {
+            (None, _) | (_, None) => Err("Path is empty".to_owned()),
+            (Some(first), _) if first != start => Err(format!(
+                "Path starts at ({}, {}) instead of fox",
+                first.x, first.y
+            )),
+            (_, Some(last)) if last != end => Err(format!(
+                "Path ends at ({}, {}) instead of flag",
+                last.x, last.y
+            )),
+            _ => movement.path_cost(map, path),
+        }
+    }
+
+    fn output_path(&mut self, path: Vec<MapPos>) -> bool {
+        let cost = self.validate(&path);
+
+        let optimal = reference::optimal_cost(&self.input);
+
+        match (&cost, optimal) {
+            (Err(err), _) => warn!("Invalid path: {}", err),
+            (Ok(cost), Some(optimal)) if *cost > optimal => {
+                warn!("Suboptimal path: cost {}, optimal {}", cost, optimal)
+            }
+            _ => {}
+        }
+
+        let color = match cost {
+            Ok(_) => Color::srgb_u8(0, 200, 0),
+            Err(_) => Color::srgb_u8(200, 0, 0),
+        };
+        let guest_layer = self.layer;
+        self.set_layer(PATH_LAYER);
+        path.windows(2).for_each(|window| {
+            if let [start, end] = window {
+                self.push(TimelineAction::Line {
+                    start: *start,
+                    end: *end,
+                    color,
+                });
+            }
+        });
+
+        self.layer = guest_layer;
+
+        let valid = cost.is_ok();
+        self.output = Some(PathOutput {
+            path,
+            cost,
+            optimal,
+            no_path: false,
+        });
+        valid
+    }
+
+    // Valid only if the reference can't reach the flag either
+    fn report_no_path(&mut self) -> bool {
+        let optimal = reference::optimal_cost(&self.input);
+        let cost = match optimal {
+            None => Ok(0),
+            Some(optimal) => {
+                let err = format!("No path reported, but flag is reachable with cost {optimal}");
+                warn!("Invalid no path: {}", err);
+                Err(err)
+            }
+        };
+
+        let valid = cost.is_ok();
+        self.output = Some(PathOutput {
+            path: Vec::new(),
+            cost,
+            optimal,
+            no_path: true,
+        });
+        valid
+    }
+
+    fn map_dimensions(&self) -> (u32, u32) {
+        (self.input.map.width(), self.input.map.height())
+    }
+
+    fn walkable(&mut self, pos: MapPos) -> bool {
+        self.query(pos);
+        self.input.map.is_walkable(pos)
+    }
+
+    fn neighbors_of(&mut self, pos: MapPos) -> Vec<(MapPos, u32)> {
+        self.query(pos);
+        self.input.movement.neighbors(&self.input.map, pos)
+    }
+
+    fn step_cost(&mut self, start: MapPos, end: MapPos) -> Option<u32> {
+        self.query(start);
+        self.input.movement.step_cost(&self.input.map, start, end)
+    }
+
+    fn enqueue_node(&mut self, pos: MapPos, priority: u32) {
+        self.search.open.insert(pos);
+        self.search.max_open = self.search.max_open.max(self.search.open.len());
+        self.push(TimelineAction::Enqueue { pos, priority });
+    }
+
+    fn expand_node(&mut self, pos: MapPos) {
+        self.search.open.remove(&pos);
+        self.search.expanded += 1;
+        self.push(TimelineAction::Expand { pos });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::block_on;
+    use wasmtime::Config;
+
+    use super::*;
+    use crate::map::MapSize;
+
+    #[test]
+    fn baseline_v1_guest_runs() {
+        let engine =
+            Engine::new(Config::new().wasm_component_model(true).async_support(true)).unwrap();
+        let component = Component::new(&engine, include_str!("fixtures/baseline_v1.wat")).unwrap();
+        let version = ApiVersion::detect(&engine, &component).unwrap();
+        assert_eq!(version, ApiVersion::V1);
+
+        let mut linker = Linker::new(&engine);
+        wasmtime_wasi::p2::add_to_linker_async(&mut linker).unwrap();
+        version.add_to_linker(&mut linker).unwrap();
+        let pre = PathfindingPre::new(version, &component, &linker).unwrap();
+
+        let input = RunInput {
+            map: Map::new(&MapSize::new(3, 2)),
+            movement: Movement::default(),
+            start: MapPos { x: 1, y: 0 },
+            end: MapPos { x: 2, y: 1 },
+        };
+        let timeline = Arc::new(Mutex::new(Timeline::default()));
+        let mut runner = WasmRunner::new(&engine, timeline.clone(), Arc::default());
+        runner.start(input.clone(), None);
+        let mut store = Store::new(&engine, runner);
+
+        block_on(async {
+            let module = pre.instantiate(&mut store).await?;
+            module.call_run(&mut store, &input).await
+        })
+        .unwrap();
+
+        let timeline = timeline.lock().unwrap();
+        assert!(matches!(
+            timeline.entries.as_slice(),
+            [TimelineEntry {
+                action: TimelineAction::Tile {
+                    pos: MapPos { x: 1, y: 0 },
+                    ..
+                },
+                ..
+            }]
+        ));
+    }
+}
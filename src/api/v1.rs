@@ -0,0 +1,108 @@
+use wasmtime::{
+    Store,
+    component::{HasSelf, Linker, bindgen},
+};
+
+use crate::{
+    api::{RunInput, TimelineAction, WasmRunner, rgb},
+    map::MapPos,
+    movement::Movement,
+};
+
+// auto-generated API from WIT
+// Exports are async so the guest can yield to Bevy mid-run
+bindgen!({
+    world: "pathfinding",
+    path: "wit/v1",
+    exports: { default: async },
+});
+
+use host::{Color, Host, Pos};
+
+// Inline interfaces in `wit/v1` have no package name or version
+pub const VERSION: &str = "1.0.0";
+pub const GUEST: &str = "guest";
+pub const HOST: &str = "host";
+
+pub fn add_to_linker(linker: &mut Linker<WasmRunner>) -> wasmtime::Result<()> {
+    host::add_to_linker::<_, HasSelf<_>>(linker, |data: &mut WasmRunner| data)
+}
+
+pub async fn call_run(
+    module: &Pathfinding,
+    store: &mut Store<WasmRunner>,
+    input: &RunInput,
+) -> wasmtime::Result<()> {
+    module
+        .guest()
+        .call_run(
+            store,
+            &input.map.to_pathfinding_map(),
+            input.start.into(),
+            input.end.into(),
+        )
+        .await
+}
+
+impl From<Movement> for host::Movement {
+    fn from(value: Movement) -> Self {
+        match value {
+            Movement::FourConnected => host::Movement::FourConnected,
+            Movement::EightConnected => host::Movement::EightConnected,
+            Movement::EightNoCornerCutting => host::Movement::EightNoCornerCutting,
+            Movement::Knight => host::Movement::Knight,
+        }
+    }
+}
+
+impl Host for WasmRunner {
+    fn tile(&mut self, pos: Pos, color: Color) {
+        self.push(TimelineAction::Tile {
+            pos: pos.into(),
+            color: rgb(color),
+        });
+    }
+
+    fn line(&mut self, start: Pos, end: Pos, color: Color) {
+        self.push(TimelineAction::Line {
+            start: start.into(),
+            end: end.into(),
+            color: rgb(color),
+        });
+    }
+
+    fn arrow(&mut self, start: Pos, end: Pos, color: Color) {
+        self.push(TimelineAction::Arrow {
+            start: start.into(),
+            end: end.into(),
+            color: rgb(color),
+        });
+    }
+
+    fn output(&mut self, path: Vec<Pos>) -> bool {
+        self.output_path(path.into_iter().map(MapPos::from).collect())
+    }
+
+    fn selected_movement(&mut self) -> host::Movement {
+        self.input.movement.into()
+    }
+
+    fn dimensions(&mut self) -> (u32, u32) {
+        self.map_dimensions()
+    }
+
+    fn is_walkable(&mut self, pos: Pos) -> bool {
+        self.walkable(pos.into())
+    }
+
+    fn neighbors(&mut self, pos: Pos) -> Vec<(Pos, u32)> {
+        self.neighbors_of(pos.into())
+            .into_iter()
+            .map(|(pos, cost)| (pos.into(), cost))
+            .collect()
+    }
+
+    fn cost(&mut self, start: Pos, end: Pos) -> Option<u32> {
+        self.step_cost(start.into(), end.into())
+    }
+}
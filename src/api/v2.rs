@@ -0,0 +1,246 @@
+use bevy::log::warn;
+use wasmtime::{
+    Store,
+    component::{HasSelf, Linker, bindgen},
+};
+
+use crate::{
+    api::{RunInput, TimelineAction, WasmRunner, rgb, rgba},
+    map::MapPos,
+    movement::Movement,
+};
+
+// auto-generated API from WIT
+// Exports are async so the guest can yield to Bevy mid-run
+bindgen!({
+    world: "pathfinding",
+    path: "wit/v2",
+    exports: { default: async },
+});
+
+use pathfinding::pathfinding::{
+    host::{self, Host},
+    types::{self, Color, Grid, Pos, Rgba},
+};
+
+pub const VERSION: &str = "2.0.0";
+
+pub fn add_to_linker(linker: &mut Linker<WasmRunner>) -> wasmtime::Result<()> {
+    types::add_to_linker::<_, HasSelf<_>>(linker, |data: &mut WasmRunner| data)?;
+    host::add_to_linker::<_, HasSelf<_>>(linker, |data: &mut WasmRunner| data)
+}
+
+pub async fn call_run(
+    module: &Pathfinding,
+    store: &mut Store<WasmRunner>,
+    input: &RunInput,
+) -> wasmtime::Result<()> {
+    let grid = Grid {
+        width: input.map.width(),
+        height: input.map.height(),
+        cells: input.map.to_cells(),
+    };
+
+    module
+        .pathfinding_pathfinding_guest()
+        .call_run(
+            store,
+            &grid,
+            input.start.into(),
+            input.end.into(),
+            input.movement.into(),
+        )
+        .await
+}
+
+impl From<Pos> for MapPos {
+    fn from(value: Pos) -> Self {
+        MapPos {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+impl From<MapPos> for Pos {
+    fn from(value: MapPos) -> Self {
+        Pos {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<Movement> for types::Movement {
+    fn from(value: Movement) -> Self {
+        match value {
+            Movement::FourConnected => types::Movement::FourConnected,
+            Movement::EightConnected => types::Movement::EightConnected,
+            Movement::EightNoCornerCutting => types::Movement::EightNoCornerCutting,
+            Movement::Knight => types::Movement::Knight,
+        }
+    }
+}
+
+impl types::Host for WasmRunner {}
+
+impl Host for WasmRunner {
+    fn tile(&mut self, pos: Pos, color: Color) {
+        self.push(TimelineAction::Tile {
+            pos: pos.into(),
+            color: rgb(color),
+        });
+    }
+
+    fn line(&mut self, start: Pos, end: Pos, color: Color) {
+        self.push(TimelineAction::Line {
+            start: start.into(),
+            end: end.into(),
+            color: rgb(color),
+        });
+    }
+
+    fn arrow(&mut self, start: Pos, end: Pos, color: Color) {
+        self.push(TimelineAction::Arrow {
+            start: start.into(),
+            end: end.into(),
+            color: rgb(color),
+        });
+    }
+
+    fn output(&mut self, path: Vec<Pos>) -> bool {
+        self.output_path(path.into_iter().map(MapPos::from).collect())
+    }
+
+    fn dimensions(&mut self) -> (u32, u32) {
+        self.map_dimensions()
+    }
+
+    fn is_walkable(&mut self, pos: Pos) -> bool {
+        self.walkable(pos.into())
+    }
+
+    fn neighbors(&mut self, pos: Pos) -> Vec<(Pos, u32)> {
+        self.neighbors_of(pos.into())
+            .into_iter()
+            .map(|(pos, cost)| (pos.into(), cost))
+            .collect()
+    }
+
+    fn cost(&mut self, start: Pos, end: Pos) -> Option<u32> {
+        self.step_cost(start.into(), end.into())
+    }
+
+    fn enqueue(&mut self, pos: Pos, priority: u32) {
+        self.enqueue_node(pos.into(), priority);
+    }
+
+    fn expand(&mut self, pos: Pos) {
+        self.expand_node(pos.into());
+    }
+
+    fn set_parent(&mut self, child: Pos, parent: Pos) {
+        self.push(TimelineAction::SetParent {
+            child: child.into(),
+            parent: parent.into(),
+        });
+    }
+
+    fn found(&mut self, pos: Pos) {
+        self.push(TimelineAction::Found { pos: pos.into() });
+    }
+
+    fn label(&mut self, pos: Pos, text: String) {
+        self.push(TimelineAction::Label {
+            pos: pos.into(),
+            text,
+        });
+    }
+
+    fn values(&mut self, pos: Pos, values: Vec<f32>) {
+        self.push(TimelineAction::Values {
+            pos: pos.into(),
+            values,
+        });
+    }
+
+    fn tile_rgba(&mut self, pos: Pos, color: Rgba) {
+        self.push(TimelineAction::Tile {
+            pos: pos.into(),
+            color: rgba(color),
+        });
+    }
+
+    fn rect(&mut self, min: Pos, max: Pos, color: Rgba, filled: bool) {
+        self.push(TimelineAction::Rect {
+            min: min.into(),
+            max: max.into(),
+            color: rgba(color),
+            filled,
+        });
+    }
+
+    fn circle(&mut self, center: Pos, radius: f32, color: Rgba, filled: bool) {
+        self.push(TimelineAction::Circle {
+            center: center.into(),
+            radius,
+            color: rgba(color),
+            filled,
+        });
+    }
+
+    fn polyline(&mut self, points: Vec<Pos>, color: Rgba) {
+        self.push(TimelineAction::Polyline {
+            points: points.into_iter().map(MapPos::from).collect(),
+            color: rgba(color),
+        });
+    }
+
+    fn layer(&mut self, name: String) {
+        self.set_layer(&name);
+    }
+
+    fn scalar(&mut self, pos: Pos, value: f32) {
+        self.push(TimelineAction::Scalar {
+            pos: pos.into(),
+            value,
+        });
+    }
+
+    fn scalar_field(&mut self, values: Vec<f32>) {
+        let (width, height) = self.map_dimensions();
+        if values.len() != (width * height) as usize {
+            warn!(
+                "Scalar field has {} values, expected {}x{}",
+                values.len(),
+                width,
+                height
+            );
+            return;
+        }
+
+        self.push(TimelineAction::ScalarField { values });
+    }
+
+    fn flow_field(&mut self, directions: Vec<(i8, i8)>) {
+        let (width, height) = self.map_dimensions();
+        if directions.len() != (width * height) as usize {
+            warn!(
+                "Flow field has {} directions, expected {}x{}",
+                directions.len(),
+                width,
+                height
+            );
+            return;
+        }
+
+        self.push(TimelineAction::FlowField { directions });
+    }
+
+    fn breakpoint(&mut self, label: String) {
+        self.guest_breakpoint(&label);
+    }
+
+    fn no_path(&mut self) -> bool {
+        self.report_no_path()
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    cmp::Reverse,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::log::{info, warn};
+use sha2::{Digest, Sha256};
+use wasmtime::{Engine, component::Component};
+
+const CACHE_DIR: &str = ".wasm-cache";
+const EXTENSION: &str = "cwasm";
+// Compiled components are big, older ones are removed
+const MAX_ENTRIES: usize = 32;
+
+// `DefaultHasher` may change between Rust releases, sha256 of the hashed bytes doesn't
+struct Sha256Hasher(Sha256);
+impl Hasher for Sha256Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let digest = self.0.clone().finalize();
+        u64::from_be_bytes(digest[..8].try_into().unwrap())
+    }
+}
+
+// Changes with wasmtime version and engine config, old entries are useless then
+fn compatibility(engine: &Engine) -> String {
+    let mut hasher = Sha256Hasher(Sha256::new());
+    engine.precompile_compatibility_hash().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// `<sha256 of the .wasm>-<compatibility>.cwasm`
+fn entry_path(engine: &Engine, bytes: &[u8]) -> PathBuf {
+    let digest = Sha256::digest(bytes);
+    let hash: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+
+    Path::new(CACHE_DIR).join(format!("{hash}-{}.{EXTENSION}", compatibility(engine)))
+}
+
+// Compiles the component once per content, later loads only map the cached code
+pub fn load_component(engine: &Engine, file: &Path) -> wasmtime::Result<Component> {
+    let bytes = fs::read(file)?;
+    let entry = entry_path(engine, &bytes);
+
+    if entry.exists() {
+        // SAFETY: cache only contains output of `Component::serialize`, entry name
+        // includes the engine compatibility hash and deserialize checks it again
+        match unsafe { Component::deserialize_file(engine, &entry) } {
+            Ok(component) => {
+                info!("Loaded {} from cache", file.display());
+                // Pruning keeps recently used entries
+                if let Err(err) = touch(&entry) {
+                    warn!("Can't touch {}: {}", entry.display(), err);
+                }
+                return Ok(component);
+            }
+            Err(err) => warn!("Ignoring cached {}: {}", entry.display(), err),
+        }
+    }
+
+    let component = Component::new(engine, &bytes)?;
+    if let Err(err) = store(engine, &entry, &component) {
+        warn!("Can't cache {}: {}", file.display(), err);
+    }
+    Ok(component)
+}
+
+fn touch(entry: &Path) -> io::Result<()> {
+    File::options()
+        .append(true)
+        .open(entry)?
+        .set_modified(SystemTime::now())
+}
+
+fn store(engine: &Engine, entry: &Path, component: &Component) -> wasmtime::Result<()> {
+    fs::create_dir_all(CACHE_DIR)?;
+
+    // Renamed when complete, so a crash can't leave half of a file
+    let partial = entry.with_extension("partial");
+    fs::write(&partial, component.serialize()?)?;
+    fs::rename(&partial, entry)?;
+
+    prune(engine)?;
+    Ok(())
+}
+
+// Removes entries of other wasmtime versions and the least recently used ones above `MAX_ENTRIES`
+fn prune(engine: &Engine) -> io::Result<()> {
+    let suffix = format!("-{}.{EXTENSION}", compatibility(engine));
+    let mut entries = Vec::new();
+
+    for dir_entry in fs::read_dir(CACHE_DIR)? {
+        let path = dir_entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        if name.ends_with(&suffix) {
+            entries.push((fs::metadata(&path)?.modified()?, path));
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    entries.sort_by_key(|entry| Reverse(entry.0));
+    for (_, path) in entries.iter().skip(MAX_ENTRIES) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
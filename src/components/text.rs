@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
-pub fn text(text: &str, size: f32) -> impl Bundle {
+// `use<>` - bundle owns the text, so it can be built from borrowed strings
+pub fn text(text: &str, size: f32) -> impl Bundle + use<> {
     (
         Text::new(text),
         TextFont {
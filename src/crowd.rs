@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+
+use crate::{
+    SPRITE_SIZE,
+    goals::Flag,
+    map::{Map, MapPos},
+    wasm::tile_center,
+};
+
+const AGENTS: usize = 100;
+// Tiles per second
+const AGENT_SPEED: f32 = 4.;
+const AGENT_RADIUS: f32 = SPRITE_SIZE as f32 / 6.;
+// Agents are spread inside a tile so the crowd is visible
+const AGENT_SPREAD: f32 = SPRITE_SIZE as f32 / 3.;
+// Above goals
+const AGENT_Z: f32 = 2.;
+
+const MOVING_COLOR: Color = Color::srgb(0.2, 0.3, 0.9);
+const ARRIVED_COLOR: Color = Color::srgb(0.1, 0.7, 0.2);
+const STUCK_COLOR: Color = Color::srgb(0.9, 0.1, 0.1);
+
+// Latest flow field of visible layers, row-major offsets to the next tile
+// (0, 0) means there is no direction for the tile
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct FlowField(pub Vec<(i8, i8)>);
+impl FlowField {
+    pub fn next(&self, map: &Map, pos: MapPos) -> Option<MapPos> {
+        let index = (pos.y * map.width() + pos.x) as usize;
+        let (dx, dy) = *self.0.get(index)?;
+        if (dx, dy) == (0, 0) {
+            return None;
+        }
+
+        let next = MapPos {
+            x: pos.x.checked_add_signed(dx as i32)?,
+            y: pos.y.checked_add_signed(dy as i32)?,
+        };
+        map.is_walkable(next).then_some(next)
+    }
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+pub struct Crowd {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentState {
+    Moving,
+    Arrived,
+    // No direction, wall ahead or walking in circles
+    Stuck,
+}
+
+#[derive(Component)]
+pub struct Agent {
+    tile: MapPos,
+    next: MapPos,
+    offset: Vec2,
+    steps: u32,
+    pub state: AgentState,
+}
+
+fn crowd_spawn(
+    mut commands: Commands,
+    crowd: Res<Crowd>,
+    field: Res<FlowField>,
+    map: Res<Map>,
+    agents: Query<Entity, With<Agent>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    agents.iter().for_each(|a| commands.entity(a).despawn());
+
+    if !crowd.enabled || field.0.is_empty() {
+        return;
+    }
+
+    let floor: Vec<MapPos> = (0..map.height())
+        .flat_map(|y| (0..map.width()).map(move |x| MapPos { x, y }))
+        .filter(|&pos| map.is_walkable(pos))
+        .collect();
+    if floor.is_empty() {
+        return;
+    }
+
+    let mesh = meshes.add(Circle::new(AGENT_RADIUS));
+    let material = materials.add(MOVING_COLOR);
+
+    for _ in 0..AGENTS {
+        let tile = floor[fastrand::usize(..floor.len())];
+        let offset = vec2(fastrand::f32() - 0.5, fastrand::f32() - 0.5) * AGENT_SPREAD;
+
+        commands.spawn((
+            Agent {
+                tile,
+                next: tile,
+                offset,
+                steps: 0,
+                state: AgentState::Moving,
+            },
+            Mesh2d(mesh.clone()),
+            MeshMaterial2d(material.clone()),
+            Transform::from_translation((tile_center(tile) + offset).extend(AGENT_Z)),
+        ));
+    }
+}
+
+fn crowd_move(
+    time: Res<Time>,
+    field: Res<FlowField>,
+    map: Res<Map>,
+    flag: Single<&MapPos, With<Flag>>,
+    mut agents: Query<(
+        &mut Agent,
+        &mut Transform,
+        &mut MeshMaterial2d<ColorMaterial>,
+    )>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let step = AGENT_SPEED * SPRITE_SIZE as f32 * time.delta_secs();
+    // Longest path without loops visits every tile once
+    let max_steps = map.width() * map.height();
+
+    for (mut agent, mut transform, mut material) in agents.iter_mut() {
+        if agent.state != AgentState::Moving {
+            continue;
+        }
+
+        let target = tile_center(agent.next) + agent.offset;
+        let position = transform.translation.xy();
+        if position.distance(target) > step {
+            let position = position.move_towards(target, step);
+            transform.translation = position.extend(AGENT_Z);
+            continue;
+        }
+        transform.translation = target.extend(AGENT_Z);
+        agent.tile = agent.next;
+
+        let state = if agent.tile == **flag {
+            AgentState::Arrived
+        } else if agent.steps >= max_steps {
+            AgentState::Stuck
+        } else if let Some(next) = field.next(&map, agent.tile) {
+            agent.next = next;
+            agent.steps += 1;
+            AgentState::Moving
+        } else {
+            AgentState::Stuck
+        };
+
+        if state != agent.state {
+            agent.state = state;
+            material.0 = materials.add(match state {
+                AgentState::Moving => MOVING_COLOR,
+                AgentState::Arrived => ARRIVED_COLOR,
+                AgentState::Stuck => STUCK_COLOR,
+            });
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct CrowdText;
+
+fn crowd_text_update(
+    crowd: Res<Crowd>,
+    agents: Query<&Agent>,
+    mut crowd_text: Single<&mut Text, With<CrowdText>>,
+) {
+    if !crowd.enabled {
+        crowd_text.0 = "Crowd: off".to_owned();
+        return;
+    }
+
+    let count = |state| agents.iter().filter(|a| a.state == state).count();
+    crowd_text.0 = format!(
+        "Crowd: {}/{} arrived, {} stuck",
+        count(AgentState::Arrived),
+        agents.iter().count(),
+        count(AgentState::Stuck)
+    );
+}
+
+pub struct CrowdPlugin;
+impl Plugin for CrowdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>()
+            .init_resource::<Crowd>()
+            .add_systems(
+                Update,
+                (
+                    // Agents start over on every new field
+                    crowd_spawn.run_if(|crowd: Res<Crowd>, field: Res<FlowField>| {
+                        crowd.is_changed() || field.is_changed()
+                    }),
+                    crowd_move,
+                    crowd_text_update,
+                )
+                    .chain(),
+            );
+    }
+}
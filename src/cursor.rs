@@ -1,6 +1,9 @@
-use bevy::{input::mouse::MouseWheel, prelude::*};
+use bevy::{input::mouse::MouseWheel, prelude::*, ui::RelativeCursorPosition};
 
-use crate::map::{Map, MapPos, MapSize, TileType};
+use crate::{
+    map::{Map, MapPos, MapSize, TileType},
+    ui::SidePanel,
+};
 
 #[derive(Resource, Default)]
 pub struct CursorPos(pub Vec2);
@@ -75,7 +78,13 @@ fn cursor_placing(
 fn middle_zoom(
     mut mouse_ev: MessageReader<MouseWheel>,
     mut camera: Single<&mut Transform, With<Camera>>,
+    panel: Query<&RelativeCursorPosition, With<SidePanel>>,
 ) {
+    // Wheel over the side panel scrolls it
+    if panel.iter().any(|cursor| cursor.cursor_over()) {
+        mouse_ev.clear();
+        return;
+    }
     for ev in mouse_ev.read() {
         camera.scale *= Vec2::splat(1.0 - ev.y * 0.1).extend(1.0);
     }
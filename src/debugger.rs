@@ -0,0 +1,112 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{
+    SPRITE_SIZE,
+    cursor::CursorPos,
+    map::{MapPos, MapSize},
+    wasm::{WasmPathfinding, tile_center},
+};
+
+const MARKER_COLOR: Color = Color::srgb(0.9, 0.1, 0.1);
+
+// Guest is paused when one of them holds, guest `breakpoint(label)` always pauses
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Breakpoints {
+    // Any action that recolors this tile
+    pub tile: Option<MapPos>,
+    // Host call with this number, counted from 1
+    pub calls: Option<u32>,
+}
+impl fmt::Display for Breakpoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tile {
+            Some(tile) => writeln!(f, "Break on tile: ({}, {})", tile.x, tile.y)?,
+            None => writeln!(f, "Break on tile: none")?,
+        }
+        match self.calls {
+            Some(calls) => write!(f, "Break on call: {calls}"),
+            None => write!(f, "Break on call: none"),
+        }
+    }
+}
+
+// Why the guest is paused, shown in UI
+#[derive(Resource, Debug, Clone, Default)]
+pub struct BreakpointHit(pub Option<String>);
+
+// B toggles the tile under cursor
+fn breakpoint_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor_pos: Res<CursorPos>,
+    size: Res<MapSize>,
+    mut breakpoints: ResMut<Breakpoints>,
+) {
+    if !keys.just_pressed(KeyCode::KeyB) {
+        return;
+    }
+
+    let map_pos: MapPos = Transform::from_translation(cursor_pos.0.extend(0.)).into();
+    let pos = map_pos.clamp(&size);
+    breakpoints.tile = match breakpoints.tile == Some(pos) {
+        true => None,
+        false => Some(pos),
+    };
+}
+
+fn breakpoint_marker(mut gizmos: Gizmos, breakpoints: Res<Breakpoints>) {
+    if let Some(tile) = breakpoints.tile {
+        gizmos.rect_2d(
+            tile_center(tile),
+            Vec2::splat(SPRITE_SIZE as f32),
+            MARKER_COLOR,
+        );
+    }
+}
+
+// Also applies to a paused guest
+fn breakpoints_sync(breakpoints: Res<Breakpoints>, pathfinding: Res<WasmPathfinding>) {
+    pathfinding.set_breakpoints(*breakpoints);
+}
+
+#[derive(Component)]
+pub struct BreakpointText;
+
+fn breakpoint_text_update(
+    breakpoints: Res<Breakpoints>,
+    hit: Res<BreakpointHit>,
+    mut breakpoint_text: Single<&mut Text, With<BreakpointText>>,
+) {
+    breakpoint_text.0 = match &hit.0 {
+        Some(reason) => format!("{}\nPaused: {}", *breakpoints, reason),
+        None => breakpoints.to_string(),
+    };
+}
+
+pub struct DebuggerPlugin;
+impl Plugin for DebuggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Breakpoints>()
+            .init_resource::<BreakpointHit>()
+            .add_systems(Update, (breakpoint_toggle, breakpoint_marker))
+            .add_systems(
+                Update,
+                breakpoints_sync.run_if(
+                    |breakpoints: Res<Breakpoints>, pathfinding: Option<Res<WasmPathfinding>>| {
+                        pathfinding.is_some_and(|pathfinding| {
+                            breakpoints.is_changed() || pathfinding.is_changed()
+                        })
+                    },
+                ),
+            )
+            .add_systems(
+                Update,
+                breakpoint_text_update.run_if(
+                    |breakpoints: Res<Breakpoints>, hit: Res<BreakpointHit>| {
+                        breakpoints.is_changed() || hit.is_changed()
+                    },
+                ),
+            );
+    }
+}
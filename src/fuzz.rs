@@ -0,0 +1,401 @@
+use std::{
+    fmt, mem,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    api::{PathOutput, RunInput},
+    goals::{Flag, Fox},
+    map::{self, MIN_MAP_SIZE, Map, MapPos, MapSize, TileType},
+    movement::Movement,
+    reference,
+    wasm::{CheckTimeout, WasmPathfinding, WasmState},
+};
+
+// Random maps are small, so failures are easy to read even before shrinking
+const MIN_SIZE: u32 = 4;
+const MAX_SIZE: u32 = 24;
+const MAX_WALL_DENSITY: f32 = 0.45;
+// Time spent on cases every frame, UI stays responsive
+const FRAME_BUDGET: Duration = Duration::from_millis(20);
+// Guest runs spent on shrinking a failure
+const MAX_SHRINK_RUNS: u32 = 2000;
+// Cases are tiny, a guest still running after this is stuck
+const CASE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Guest output that doesn't match the reference solver
+#[derive(Debug, Clone, PartialEq)]
+pub enum Disagreement {
+    Trap(String),
+    Timeout,
+    Invalid(String),
+    // Guest gave or reported no path, but the flag is reachable
+    MissedPath { optimal: u32 },
+    Suboptimal { cost: u32, optimal: u32 },
+}
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Disagreement::Trap(err) => write!(f, "Trap: {err}"),
+            Disagreement::Timeout => {
+                write!(f, "Timeout: no result in {} ms", CASE_TIMEOUT.as_millis())
+            }
+            Disagreement::Invalid(err) => write!(f, "Invalid path: {err}"),
+            Disagreement::MissedPath { optimal } => {
+                write!(f, "No path, but reference found cost {optimal}")
+            }
+            Disagreement::Suboptimal { cost, optimal } => {
+                write!(f, "Cost {cost}, optimal {optimal}")
+            }
+        }
+    }
+}
+
+fn compare(wasm: &WasmPathfinding, input: &RunInput) -> Option<Disagreement> {
+    let optimal = reference::optimal_cost(input);
+
+    match (wasm.check(input.clone(), CASE_TIMEOUT), optimal) {
+        (Err(err), _) if err.downcast_ref::<CheckTimeout>().is_some() => {
+            Some(Disagreement::Timeout)
+        }
+        (Err(err), _) => Some(Disagreement::Trap(err.to_string())),
+        (Ok(None), Some(optimal)) => Some(Disagreement::MissedPath { optimal }),
+        (Ok(Some(output)), Some(optimal)) if output.is_no_path() => {
+            Some(Disagreement::MissedPath { optimal })
+        }
+        (Ok(Some(PathOutput { cost: Err(err), .. })), _) => Some(Disagreement::Invalid(err)),
+        (Ok(Some(PathOutput { cost: Ok(cost), .. })), Some(optimal)) if cost > optimal => {
+            Some(Disagreement::Suboptimal { cost, optimal })
+        }
+        _ => None,
+    }
+}
+
+fn random_case(rng: &mut fastrand::Rng, movement: Movement) -> RunInput {
+    let size = MapSize::new(rng.u32(MIN_SIZE..=MAX_SIZE), rng.u32(MIN_SIZE..=MAX_SIZE));
+    let mut map = Map::new(&size);
+
+    let density = rng.f32() * MAX_WALL_DENSITY;
+    for tile in map.0.iter_mut().flatten() {
+        if rng.f32() < density {
+            tile.tile_type = TileType::Wall;
+        }
+    }
+
+    let mut random_pos = || MapPos {
+        x: rng.u32(..size.0.x),
+        y: rng.u32(..size.0.y),
+    };
+    let start = random_pos();
+    let mut end = random_pos();
+    while end == start {
+        end = random_pos();
+    }
+    map.get_tile_mut(&start).tile_type = TileType::Floor;
+    map.get_tile_mut(&end).tile_type = TileType::Floor;
+
+    RunInput {
+        map,
+        movement,
+        start,
+        end,
+    }
+}
+
+// Map without one of its edges, goals have to stay on the map
+fn crops(case: &RunInput) -> Vec<RunInput> {
+    let (width, height) = (case.map.width(), case.map.height());
+    let goals = [case.start, case.end];
+    let mut crops = Vec::new();
+
+    if width > MIN_MAP_SIZE && goals.iter().all(|pos| pos.x > 0) {
+        let mut crop = case.clone();
+        crop.map.0.iter_mut().for_each(|row| {
+            row.remove(0);
+        });
+        crop.start.x -= 1;
+        crop.end.x -= 1;
+        crops.push(crop);
+    }
+    if width > MIN_MAP_SIZE && goals.iter().all(|pos| pos.x < width - 1) {
+        let mut crop = case.clone();
+        crop.map.0.iter_mut().for_each(|row| {
+            row.pop();
+        });
+        crops.push(crop);
+    }
+    if height > MIN_MAP_SIZE && goals.iter().all(|pos| pos.y > 0) {
+        let mut crop = case.clone();
+        crop.map.0.remove(0);
+        crop.start.y -= 1;
+        crop.end.y -= 1;
+        crops.push(crop);
+    }
+    if height > MIN_MAP_SIZE && goals.iter().all(|pos| pos.y < height - 1) {
+        let mut crop = case.clone();
+        crop.map.0.pop();
+        crops.push(crop);
+    }
+    crops
+}
+
+// Map with one wall less
+fn wall_removals(case: &RunInput) -> Vec<RunInput> {
+    (0..case.map.height())
+        .flat_map(|y| (0..case.map.width()).map(move |x| MapPos { x, y }))
+        .filter(|pos| !case.map.is_walkable(*pos))
+        .map(|pos| {
+            let mut removal = case.clone();
+            removal.map.get_tile_mut(&pos).tile_type = TileType::Floor;
+            removal
+        })
+        .collect()
+}
+
+// Smaller cases to try, in the order they are tried
+fn candidates(case: &RunInput) -> Vec<RunInput> {
+    let mut candidates: Vec<RunInput> =
+        crops(case).into_iter().chain(wall_removals(case)).collect();
+    // Popped from the end
+    candidates.reverse();
+    candidates
+}
+
+// Greedy: crops edges and removes walls while the same kind of disagreement stays,
+// resumed every frame
+#[derive(Debug)]
+struct Shrinking {
+    case: RunInput,
+    found: Disagreement,
+    original_size: (u32, u32),
+    // Untried candidates of `case`
+    candidates: Vec<RunInput>,
+    runs: u32,
+}
+impl Shrinking {
+    fn new(case: RunInput, found: Disagreement) -> Shrinking {
+        Shrinking {
+            original_size: (case.map.width(), case.map.height()),
+            candidates: candidates(&case),
+            case,
+            found,
+            runs: 0,
+        }
+    }
+
+    // Tries one candidate, `false` once there is nothing left to try
+    fn step(&mut self, wasm: &WasmPathfinding) -> bool {
+        // Each run of a stuck guest blocks the frame for `CASE_TIMEOUT`, timeouts are kept as found
+        if self.found == Disagreement::Timeout || self.runs >= MAX_SHRINK_RUNS {
+            return false;
+        }
+        let Some(candidate) = self.candidates.pop() else {
+            return false;
+        };
+        self.runs += 1;
+
+        if let Some(disagreement) = compare(wasm, &candidate)
+            && mem::discriminant(&disagreement) == mem::discriminant(&self.found)
+        {
+            self.candidates = candidates(&candidate);
+            self.case = candidate;
+            self.found = disagreement;
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FuzzFailure {
+    // Case number, counted from 1
+    pub case: u32,
+    pub disagreement: Disagreement,
+    pub original_size: (u32, u32),
+    pub shrunk_size: (u32, u32),
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Fuzzing {
+    pub running: bool,
+    pub cases: u32,
+    pub failure: Option<FuzzFailure>,
+    shrinking: Option<Shrinking>,
+    rng: fastrand::Rng,
+}
+impl Fuzzing {
+    pub fn toggle(&mut self) {
+        match self.running {
+            true => {
+                self.running = false;
+                self.shrinking = None;
+            }
+            false => {
+                self.running = true;
+                self.cases = 0;
+                self.failure = None;
+                self.shrinking = None;
+            }
+        }
+    }
+}
+impl fmt::Display for Fuzzing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(shrinking) = &self.shrinking {
+            return write!(
+                f,
+                "Case {} failed: {}\nShrinking {}x{}, {} runs",
+                self.cases,
+                shrinking.found,
+                shrinking.case.map.width(),
+                shrinking.case.map.height(),
+                shrinking.runs
+            );
+        }
+
+        match (&self.failure, self.running) {
+            (Some(failure), _) => write!(
+                f,
+                "Case {} failed: {}\nShrunk {}x{} -> {}x{}",
+                failure.case,
+                failure.disagreement,
+                failure.original_size.0,
+                failure.original_size.1,
+                failure.shrunk_size.0,
+                failure.shrunk_size.1
+            ),
+            (None, true) => write!(f, "Fuzzing: {} cases", self.cases),
+            (None, false) if self.cases > 0 => {
+                write!(f, "No disagreement in {} cases", self.cases)
+            }
+            (None, false) => write!(f, "Fuzzing: off"),
+        }
+    }
+}
+
+// Stops on the first disagreement and opens its shrunk map in the editor
+fn fuzz_step(
+    mut commands: Commands,
+    mut fuzzing: ResMut<Fuzzing>,
+    wasm: Res<WasmPathfinding>,
+    movement: Res<Movement>,
+    fox: Single<Entity, With<Fox>>,
+    flag: Single<Entity, With<Flag>>,
+    mut mut_state: ResMut<NextState<WasmState>>,
+) {
+    let started = Instant::now();
+
+    while started.elapsed() < FRAME_BUDGET {
+        if let Some(shrinking) = fuzzing.shrinking.as_mut() {
+            if shrinking.step(&wasm) {
+                continue;
+            }
+
+            let Shrinking {
+                case,
+                found: disagreement,
+                original_size,
+                ..
+            } = fuzzing.shrinking.take().unwrap();
+            let shrunk_size = (case.map.width(), case.map.height());
+            info!(
+                "Shrunk to {}x{}: {}",
+                shrunk_size.0, shrunk_size.1, disagreement
+            );
+
+            fuzzing.running = false;
+            fuzzing.failure = Some(FuzzFailure {
+                case: fuzzing.cases,
+                disagreement,
+                original_size,
+                shrunk_size,
+            });
+
+            // Guest runs it again to draw it
+            map::load_map(&mut commands, case.map);
+            commands.entity(*fox).insert(case.start);
+            commands.entity(*flag).insert(case.end);
+            mut_state.set(WasmState::Run);
+            return;
+        }
+
+        let case = random_case(&mut fuzzing.rng, *movement);
+        fuzzing.cases += 1;
+
+        if let Some(disagreement) = compare(&wasm, &case) {
+            warn!("Fuzz case {} failed: {}", fuzzing.cases, disagreement);
+            fuzzing.shrinking = Some(Shrinking::new(case, disagreement));
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct FuzzText;
+
+fn fuzz_text_update(fuzzing: Res<Fuzzing>, mut fuzz_text: Single<&mut Text, With<FuzzText>>) {
+    fuzz_text.0 = fuzzing.to_string();
+}
+
+pub struct FuzzPlugin;
+impl Plugin for FuzzPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Fuzzing>()
+            .add_systems(
+                Update,
+                fuzz_step.run_if(
+                    |fuzzing: Res<Fuzzing>, pathfinding: Option<Res<WasmPathfinding>>| {
+                        fuzzing.running && pathfinding.is_some()
+                    },
+                ),
+            )
+            .add_systems(
+                Update,
+                fuzz_text_update.run_if(|fuzzing: Res<Fuzzing>| fuzzing.is_changed()),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(text: &str) -> RunInput {
+        let (map, start, end) = Map::from_ascii(text).unwrap();
+        RunInput {
+            map,
+            movement: Movement::default(),
+            start: start.unwrap(),
+            end: end.unwrap(),
+        }
+    }
+
+    #[test]
+    fn crops_keep_goals_on_map() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        for _ in 0..100 {
+            let case = random_case(&mut rng, Movement::default());
+            for crop in crops(&case) {
+                let (width, height) = (crop.map.width(), crop.map.height());
+                assert!(width >= MIN_MAP_SIZE && height >= MIN_MAP_SIZE);
+                assert!(crop.map.0.iter().all(|row| row.len() == width as usize));
+                for pos in [crop.start, crop.end] {
+                    assert!(pos.x < width && pos.y < height);
+                    assert!(crop.map.is_walkable(pos));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn edges_with_goals_are_not_cropped() {
+        let middle = case("....\n.S..\n..G.\n....\n");
+        let crops_of_middle = crops(&middle);
+        assert_eq!(crops_of_middle.len(), 4);
+        // Left edge goes first, goals move with the map
+        assert_eq!(crops_of_middle[0].start, MapPos { x: 0, y: 2 });
+        assert_eq!(crops_of_middle[0].end, MapPos { x: 1, y: 1 });
+
+        assert!(crops(&case("S...\n....\n...G\n")).is_empty());
+    }
+}
@@ -12,17 +12,28 @@ pub struct Fox;
 #[derive(Component)]
 pub struct Flag;
 
+// Walking animation, frames are laid out horizontally, first one is idle
+pub const FOX_FRAMES: u32 = 4;
+
 fn map_load(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     size: Res<MapSize>,
     mut picking_settings: ResMut<SpritePickingSettings>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
     picking_settings.picking_mode = SpritePickingMode::BoundingBox;
 
+    let fox_layout =
+        TextureAtlasLayout::from_grid(UVec2::splat(SPRITE_SIZE), FOX_FRAMES, 1, None, None);
+
     commands.spawn((
         Sprite {
-            image: asset_server.load("fox.png"),
+            image: asset_server.load("fox_walk.png"),
+            texture_atlas: Some(TextureAtlas {
+                layout: layouts.add(fox_layout),
+                index: 0,
+            }),
             custom_size: Some(Vec2::splat(SPRITE_SIZE as f32 * 1.1)), // make bigger to be seen better
             ..default()
         },
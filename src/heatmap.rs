@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+
+const LEGEND_STEPS: usize = 16;
+
+// Evenly spaced stops, sampled with linear interpolation
+const VIRIDIS: [Srgba; 5] = [
+    Srgba::rgb(0.267, 0.005, 0.329),
+    Srgba::rgb(0.231, 0.322, 0.545),
+    Srgba::rgb(0.129, 0.569, 0.549),
+    Srgba::rgb(0.369, 0.788, 0.384),
+    Srgba::rgb(0.992, 0.906, 0.145),
+];
+// Stops are sampled from matplotlib, 0.318 is not meant as 1/π
+#[allow(clippy::approx_constant)]
+const MAGMA: [Srgba; 5] = [
+    Srgba::rgb(0.001, 0.000, 0.016),
+    Srgba::rgb(0.318, 0.071, 0.486),
+    Srgba::rgb(0.718, 0.216, 0.475),
+    Srgba::rgb(0.988, 0.537, 0.380),
+    Srgba::rgb(0.988, 0.992, 0.749),
+];
+const DIVERGING: [Srgba; 5] = [
+    Srgba::rgb(0.129, 0.400, 0.675),
+    Srgba::rgb(0.404, 0.663, 0.812),
+    Srgba::rgb(0.969, 0.969, 0.969),
+    Srgba::rgb(0.937, 0.541, 0.384),
+    Srgba::rgb(0.698, 0.094, 0.169),
+];
+
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Magma,
+    // Centered at zero, for signed fields
+    Diverging,
+}
+impl Colormap {
+    pub fn next(self) -> Colormap {
+        match self {
+            Colormap::Viridis => Colormap::Magma,
+            Colormap::Magma => Colormap::Diverging,
+            Colormap::Diverging => Colormap::Viridis,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colormap::Viridis => "viridis",
+            Colormap::Magma => "magma",
+            Colormap::Diverging => "diverging",
+        }
+    }
+
+    fn stops(self) -> &'static [Srgba] {
+        match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Diverging => &DIVERGING,
+        }
+    }
+
+    // `t` in 0..=1
+    pub fn sample(self, t: f32) -> Color {
+        let stops = self.stops();
+        let scaled = t.clamp(0., 1.) * (stops.len() - 1) as f32;
+        let index = (scaled as usize).min(stops.len() - 2);
+
+        stops[index]
+            .mix(&stops[index + 1], scaled - index as f32)
+            .into()
+    }
+
+    // Maps value into 0..=1 of the range shown in legend
+    pub fn normalize(self, range: &ScalarRange, value: f32) -> f32 {
+        let (min, max) = self.bounds(range);
+        match max > min {
+            true => (value - min) / (max - min),
+            false => 0.5,
+        }
+    }
+
+    pub fn color(self, range: &ScalarRange, value: f32) -> Color {
+        self.sample(self.normalize(range, value))
+    }
+
+    fn bounds(self, range: &ScalarRange) -> (f32, f32) {
+        match self {
+            Colormap::Diverging => {
+                let limit = range.min.abs().max(range.max.abs());
+                (-limit, limit)
+            }
+            _ => (range.min, range.max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalarRange {
+    pub min: f32,
+    pub max: f32,
+}
+impl ScalarRange {
+    // NaN values mean "no value" and are skipped
+    pub fn from_values(values: impl IntoIterator<Item = f32>) -> Option<ScalarRange> {
+        values
+            .into_iter()
+            .filter(|value| !value.is_nan())
+            .fold(None, |range, value| {
+                Some(match range {
+                    None => ScalarRange {
+                        min: value,
+                        max: value,
+                    },
+                    Some(ScalarRange { min, max }) => ScalarRange {
+                        min: min.min(value),
+                        max: max.max(value),
+                    },
+                })
+            })
+    }
+}
+
+// Range of scalars in visible layers, `None` if there are none
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Heatmap(pub Option<ScalarRange>);
+
+#[derive(Component)]
+pub struct HeatmapLegend;
+
+fn heatmap_legend_update(
+    mut commands: Commands,
+    heatmap: Res<Heatmap>,
+    colormap: Res<Colormap>,
+    legend: Single<(Entity, &mut Node), With<HeatmapLegend>>,
+) {
+    let (entity, mut node) = legend.into_inner();
+    commands.entity(entity).despawn_related::<Children>();
+
+    let Some(range) = heatmap.0 else {
+        node.display = Display::None;
+        return;
+    };
+    node.display = Display::Flex;
+
+    let (min, max) = colormap.bounds(&range);
+    let colormap = *colormap;
+
+    commands.entity(entity).with_children(|legend| {
+        legend.spawn(text(&format!("{min:.1}"), 20.));
+        legend
+            .spawn(Node {
+                display: Display::Flex,
+                flex_grow: 1.,
+                height: px(12),
+                ..default()
+            })
+            .with_children(|bar| {
+                for step in 0..LEGEND_STEPS {
+                    bar.spawn((
+                        Node {
+                            flex_grow: 1.,
+                            ..default()
+                        },
+                        BackgroundColor(colormap.sample(step as f32 / (LEGEND_STEPS - 1) as f32)),
+                    ));
+                }
+            });
+        legend.spawn(text(&format!("{max:.1}"), 20.));
+    });
+}
+
+pub struct HeatmapPlugin;
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Heatmap>()
+            .init_resource::<Colormap>()
+            .add_systems(
+                Update,
+                heatmap_legend_update.run_if(|heatmap: Res<Heatmap>, colormap: Res<Colormap>| {
+                    heatmap.is_changed() || colormap.is_changed()
+                }),
+            );
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::{
+    prelude::*,
+    ui_widgets::{Activate, observe},
+};
+
+use crate::{
+    api::{PathOutput, RunInput, Timeline},
+    components::*,
+    goals::{Flag, Fox},
+    layers::Layers,
+    map,
+    statistics::Statistics,
+    wasm::WasmPathfinding,
+};
+
+// Oldest runs are forgotten
+const MAX_RUNS: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct RunRecord {
+    // Counted from 1, stays the same when older runs are forgotten
+    pub number: usize,
+    pub algorithm: String,
+    pub input: RunInput,
+    pub timeline: Timeline,
+    pub statistics: Statistics,
+    // Trap or other error of the guest
+    pub error: Option<String>,
+}
+impl RunRecord {
+    // One line of the history list, enough to compare runs
+    fn summary(&self) -> String {
+        let outcome = match (&self.error, &self.statistics.output) {
+            (Some(_), _) => "error".to_owned(),
+            (None, None) => "no path".to_owned(),
+            (None, Some(output @ PathOutput { cost: Ok(_), .. })) if output.is_no_path() => {
+                "no path (correct)".to_owned()
+            }
+            (None, Some(output @ PathOutput { cost: Ok(cost), .. })) => {
+                match output.is_suboptimal() {
+                    true => format!("cost {cost} (suboptimal)"),
+                    false => format!("cost {cost}"),
+                }
+            }
+            (None, Some(PathOutput { cost: Err(_), .. })) => "invalid".to_owned(),
+        };
+
+        format!(
+            "#{} {} {}: {}, {} explored, {} expanded",
+            self.number,
+            self.algorithm,
+            self.input.movement.name(),
+            outcome,
+            self.statistics.queried_nodes,
+            self.statistics.expanded
+        )
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct RunHistory {
+    pub runs: Vec<RunRecord>,
+    // Number of the run drawn on the map
+    pub shown: Option<usize>,
+    last_number: usize,
+}
+impl RunHistory {
+    pub fn push(&mut self, mut record: RunRecord) {
+        self.last_number += 1;
+        record.number = self.last_number;
+        self.shown = Some(record.number);
+
+        self.runs.push(record);
+        if self.runs.len() > MAX_RUNS {
+            self.runs.remove(0);
+        }
+    }
+
+    fn get(&self, number: usize) -> Option<&RunRecord> {
+        self.runs.iter().find(|run| run.number == number)
+    }
+}
+
+#[derive(Component)]
+pub struct RunHistoryList;
+
+// UI list is rebuilt from scratch, newest run first
+fn run_history_list_update(
+    mut commands: Commands,
+    history: Res<RunHistory>,
+    list: Single<Entity, With<RunHistoryList>>,
+) {
+    commands
+        .entity(*list)
+        .despawn_related::<Children>()
+        .with_children(|list| {
+            for run in history.runs.iter().rev() {
+                let marker = match history.shown == Some(run.number) {
+                    true => "> ",
+                    false => "",
+                };
+                let number = run.number;
+                list.spawn((
+                    button(text(&format!("{marker}{}", run.summary()), 20.)),
+                    // Puts map, goals and movement of the run back and draws its timeline,
+                    // guest is not run again
+                    observe(
+                        move |_: On<Activate>,
+                              mut commands: Commands,
+                              mut history: ResMut<RunHistory>,
+                              pathfinding: Option<ResMut<WasmPathfinding>>,
+                              mut layers: ResMut<Layers>,
+                              fox: Single<Entity, With<Fox>>,
+                              flag: Single<Entity, With<Flag>>| {
+                            let (Some(run), Some(mut pathfinding)) =
+                                (history.get(number).cloned(), pathfinding)
+                            else {
+                                return;
+                            };
+
+                            let map_size = uvec2(run.input.map.width(), run.input.map.height());
+                            map::load_map(&mut commands, run.input.map);
+                            commands.entity(*fox).insert(run.input.start);
+                            commands.entity(*flag).insert(run.input.end);
+                            commands.insert_resource(run.input.movement);
+                            commands.insert_resource(run.statistics);
+
+                            pathfinding.restore_timeline(
+                                run.timeline.entries,
+                                run.timeline.layers.clone(),
+                                map_size,
+                            );
+                            layers.sync(&run.timeline.layers);
+                            history.shown = Some(number);
+                        },
+                    ),
+                ));
+            }
+        });
+}
+
+pub struct RunHistoryPlugin;
+impl Plugin for RunHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunHistory>().add_systems(
+            Update,
+            run_history_list_update.run_if(|history: Res<RunHistory>| history.is_changed()),
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{SPRITE_SIZE, map::MapPos};
+
+// Hide labels when zoomed out further than this, they are unreadable anyway
+const MAX_CAMERA_SCALE: f32 = 1.5;
+// Render big font and scale it down to keep text sharp
+const FONT_SIZE: f32 = 24.;
+const FONT_SCALE: f32 = 0.2;
+
+#[derive(Debug, Clone, Default)]
+pub struct TileLabel {
+    pub text: Option<String>,
+    pub values: Vec<f32>,
+}
+
+// Latest label of every tile, filled from the timeline
+#[derive(Resource, Debug, Clone, Default)]
+pub struct TileLabels(pub HashMap<MapPos, TileLabel>);
+impl TileLabels {
+    pub fn max_values(&self) -> usize {
+        self.0
+            .values()
+            .map(|label| label.values.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+// What is drawn on tiles
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LabelMode {
+    #[default]
+    Text,
+    Value(usize),
+    Hidden,
+}
+impl LabelMode {
+    pub fn next(self, max_values: usize) -> LabelMode {
+        match self {
+            LabelMode::Text if max_values > 0 => LabelMode::Value(0),
+            LabelMode::Value(index) if index + 1 < max_values => LabelMode::Value(index + 1),
+            LabelMode::Hidden => LabelMode::Text,
+            _ => LabelMode::Hidden,
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            LabelMode::Text => "text".to_owned(),
+            LabelMode::Value(index) => format!("value {index}"),
+            LabelMode::Hidden => "hidden".to_owned(),
+        }
+    }
+
+    fn label_text(self, label: &TileLabel) -> Option<String> {
+        match self {
+            LabelMode::Text => label.text.clone(),
+            LabelMode::Value(index) => label.values.get(index).map(|value| {
+                if value.fract() == 0. {
+                    format!("{value}")
+                } else {
+                    format!("{value:.1}")
+                }
+            }),
+            LabelMode::Hidden => None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct TileLabelText;
+
+fn labels_render(
+    mut commands: Commands,
+    labels: Res<TileLabels>,
+    mode: Res<LabelMode>,
+    old_labels: Query<Entity, With<TileLabelText>>,
+) {
+    old_labels
+        .iter()
+        .for_each(|entity| commands.entity(entity).despawn());
+
+    for (&pos, label) in labels.0.iter() {
+        let Some(text) = mode.label_text(label) else {
+            continue;
+        };
+
+        let mut transform: Transform = pos.into();
+        transform.translation += Vec3::new(SPRITE_SIZE as f32 / 2., SPRITE_SIZE as f32 / 2., 1.);
+        transform.scale = Vec3::splat(FONT_SCALE);
+
+        commands.spawn((
+            Text2d::new(text),
+            TextFont {
+                font_size: FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            transform,
+            TileLabelText,
+        ));
+    }
+}
+
+fn labels_visibility(
+    camera: Single<&Transform, With<Camera>>,
+    mut labels: Query<&mut Visibility, With<TileLabelText>>,
+) {
+    let visibility = match camera.scale.x > MAX_CAMERA_SCALE {
+        true => Visibility::Hidden,
+        false => Visibility::Inherited,
+    };
+
+    labels.iter_mut().for_each(|mut label| {
+        label.set_if_neq(visibility);
+    });
+}
+
+pub struct LabelsPlugin;
+impl Plugin for LabelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TileLabels>()
+            .init_resource::<LabelMode>()
+            .add_systems(
+                Update,
+                (
+                    labels_render.run_if(|labels: Res<TileLabels>, mode: Res<LabelMode>| {
+                        labels.is_changed() || mode.is_changed()
+                    }),
+                    labels_visibility,
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::{
+    prelude::*,
+    ui::Checked,
+    ui_widgets::{Activate, ValueChange, observe},
+};
+
+use crate::components::*;
+
+pub const DEFAULT_LAYER: &str = "default";
+// Host draws validated path here
+pub const PATH_LAYER: &str = "path";
+
+// `None` keeps colors chosen by the guest
+pub const TINTS: [Option<Color>; 7] = [
+    None,
+    Some(Color::srgb(0.9, 0.2, 0.2)),
+    Some(Color::srgb(0.2, 0.8, 0.2)),
+    Some(Color::srgb(0.2, 0.4, 0.9)),
+    Some(Color::srgb(0.9, 0.8, 0.2)),
+    Some(Color::srgb(0.8, 0.3, 0.8)),
+    Some(Color::srgb(0.3, 0.8, 0.8)),
+];
+
+#[derive(Reflect, Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+    // Index in `TINTS`
+    pub tint: usize,
+}
+impl Layer {
+    fn new(name: &str) -> Self {
+        Layer {
+            name: name.to_owned(),
+            visible: true,
+            tint: 0,
+        }
+    }
+
+    // Unknown index means no tint
+    fn tint_color(&self) -> Option<Color> {
+        TINTS.get(self.tint).copied().flatten()
+    }
+
+    // Replaces hue of the guest color, transparency is kept
+    pub fn tinted(&self, color: Color) -> Color {
+        match self.tint_color() {
+            Some(tint) => tint.with_alpha(color.alpha()),
+            None => color,
+        }
+    }
+}
+
+// Layers in drawing order, last one is on top
+// Settings are kept between runs, matched by name
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+pub struct Layers(pub Vec<Layer>);
+impl Layers {
+    // Keeps known layers in their place, appends new ones, drops missing ones
+    pub fn sync(&mut self, names: &[String]) {
+        self.0.retain(|layer| names.contains(&layer.name));
+
+        for name in names {
+            if !self.0.iter().any(|layer| &layer.name == name) {
+                self.0.push(Layer::new(name));
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct LayersList;
+
+fn spawn_layer_row(list: &mut ChildSpawnerCommands, index: usize, layer: &Layer) {
+    list.spawn(Node {
+        display: Display::Flex,
+        width: percent(100),
+        column_gap: px(4),
+        align_items: AlignItems::Center,
+        ..default()
+    })
+    .with_children(|row| {
+        let mut visible = row.spawn((
+            checkbox(),
+            observe(
+                move |event: On<ValueChange<bool>>, mut layers: ResMut<Layers>| {
+                    layers.0[index].visible = event.value;
+                },
+            ),
+        ));
+        if layer.visible {
+            visible.insert(Checked);
+        }
+
+        row.spawn((
+            Node {
+                width: px(12),
+                height: px(12),
+                ..default()
+            },
+            BackgroundColor(layer.tint_color().unwrap_or(Color::NONE)),
+        ));
+        row.spawn((
+            Node {
+                flex_grow: 1.,
+                ..default()
+            },
+            children![text(&layer.name, 20.)],
+        ));
+        row.spawn((
+            button(text("^", 20.)),
+            observe(move |_: On<Activate>, mut layers: ResMut<Layers>| {
+                if index > 0 {
+                    layers.0.swap(index, index - 1);
+                }
+            }),
+        ));
+        row.spawn((
+            button(text("v", 20.)),
+            observe(move |_: On<Activate>, mut layers: ResMut<Layers>| {
+                if index + 1 < layers.0.len() {
+                    layers.0.swap(index, index + 1);
+                }
+            }),
+        ));
+        row.spawn((
+            button(text("Tint", 20.)),
+            observe(move |_: On<Activate>, mut layers: ResMut<Layers>| {
+                let layer = &mut layers.0[index];
+                layer.tint = (layer.tint + 1) % TINTS.len();
+            }),
+        ));
+    });
+}
+
+// UI list is rebuilt from scratch, top layer first
+fn layers_list_update(
+    mut commands: Commands,
+    layers: Res<Layers>,
+    list: Single<Entity, With<LayersList>>,
+) {
+    commands
+        .entity(*list)
+        .despawn_related::<Children>()
+        .with_children(|list| {
+            for (index, layer) in layers.0.iter().enumerate().rev() {
+                spawn_layer_row(list, index, layer);
+            }
+        });
+}
+
+pub struct LayersPlugin;
+impl Plugin for LayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Layers>().add_systems(
+            Update,
+            layers_list_update.run_if(|layers: Res<Layers>| layers.is_changed()),
+        );
+    }
+}
@@ -34,12 +34,30 @@ use winit::window::Icon;
 pub static SPRITE_SIZE: u32 = 16;
 
 mod api;
+mod cache;
 mod components;
+mod crowd;
 mod cursor;
+mod debugger;
+mod fuzz;
 mod goals;
+mod heatmap;
+mod history;
+mod labels;
+mod layers;
 mod map;
+mod map_image;
+mod movement;
+mod profiling;
+mod project;
+mod reachability;
+mod reference;
+mod share;
+mod statistics;
 mod ui;
+mod walk;
 mod wasm;
+mod workspace;
 
 fn startup(mut commands: Commands, size: Res<map::MapSize>) {
     let camera_id = commands
@@ -104,8 +122,24 @@ fn main() {
         .add_plugins((
             MeshPickingPlugin,
             map::MapHandlerPlugin,
+            map_image::MapImagePlugin,
             goals::GoalsHandlerPlugin,
+            heatmap::HeatmapPlugin,
+            crowd::CrowdPlugin,
+            walk::FoxWalkPlugin,
+            labels::LabelsPlugin,
+            layers::LayersPlugin,
+        ))
+        .add_plugins((
+            wasm::WasmRunnerPlugin,
+            profiling::ProfilingPlugin,
+            project::GuestProjectPlugin,
+            reference::ReferencePlugin,
+            fuzz::FuzzPlugin,
+            reachability::ReachabilityPlugin,
+            debugger::DebuggerPlugin,
+            history::RunHistoryPlugin,
+            workspace::WorkspacePlugin,
         ))
-        .add_plugins(wasm::WasmRunnerPlugin)
         .run();
 }
@@ -5,7 +5,15 @@ use bevy::{
 
 use crate::{cursor::CursorState, SPRITE_SIZE};
 
-#[derive(Resource, Debug, Clone)]
+// Limits of map size sliders
+pub const MIN_MAP_SIZE: u32 = 2;
+pub const MAX_MAP_SIZE: u32 = 128;
+
+// Goals in text maps
+const START_CHAR: char = 'S';
+const GOAL_CHAR: char = 'G';
+
+#[derive(Resource, Reflect, Debug, Clone, Default)]
 pub struct Map(pub Vec<Vec<TileInfo>>);
 impl Map {
     pub fn new(size: &MapSize) -> Self {
@@ -67,6 +75,31 @@ impl Map {
             .collect()
     }
 
+    // Row-major tileset indices, `y * width + x`
+    pub fn to_cells(&self) -> Vec<u8> {
+        self.0
+            .iter()
+            .flatten()
+            .map(|tile| tile.tile_type.to_index() as u8)
+            .collect()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.0.first().map_or(0, |row| row.len() as u32)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.0.len() as u32
+    }
+
+    // Out of bounds tiles are not walkable
+    pub fn is_walkable(&self, pos: MapPos) -> bool {
+        self.0
+            .get(pos.y as usize)
+            .and_then(|row| row.get(pos.x as usize))
+            .is_some_and(|tile| tile.tile_type == TileType::Floor)
+    }
+
     pub fn get_tile(&self, pos: &MapPos) -> &TileInfo {
         self.0
             .get(pos.y as usize)
@@ -82,6 +115,99 @@ impl Map {
             .get_mut(pos.x as usize)
             .unwrap()
     }
+
+    // `None` outside of the map, for positions that come from guests
+    pub fn tile_mut(&mut self, pos: &MapPos) -> Option<&mut TileInfo> {
+        self.0.get_mut(pos.y as usize)?.get_mut(pos.x as usize)
+    }
+
+    // Text map, top row first (as it looks on screen)
+    pub fn to_ascii(&self, start: MapPos, end: MapPos) -> String {
+        let mut text = String::new();
+
+        for (y, row) in self.0.iter().enumerate().rev() {
+            for (x, tile) in row.iter().enumerate() {
+                let pos = MapPos {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                text.push(match pos {
+                    _ if pos == start => START_CHAR,
+                    _ if pos == end => GOAL_CHAR,
+                    _ => tile.tile_type.to_char(),
+                });
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+
+    // Inverse of `to_ascii`, goals are returned if present
+    pub fn from_ascii(text: &str) -> Result<(Map, Option<MapPos>, Option<MapPos>), String> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let height = lines.len() as u32;
+        let width = lines.first().map_or(0, |line| line.chars().count() as u32);
+        if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&width)
+            || !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&height)
+        {
+            return Err(format!(
+                "Map is {width}x{height}, sides must be {MIN_MAP_SIZE}..={MAX_MAP_SIZE} tiles"
+            ));
+        }
+
+        let (mut start, mut end) = (None, None);
+        let mut rows = Vec::with_capacity(height as usize);
+
+        // Text starts from the top row
+        for (y, line) in lines.iter().rev().enumerate() {
+            if line.chars().count() as u32 != width {
+                return Err(format!(
+                    "Line {} is not {width} tiles wide",
+                    height as usize - y
+                ));
+            }
+
+            let mut row = Vec::with_capacity(width as usize);
+            for (x, char) in line.chars().enumerate() {
+                let pos = MapPos {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                let tile_type = match char {
+                    START_CHAR => {
+                        start = Some(pos);
+                        TileType::Floor
+                    }
+                    GOAL_CHAR => {
+                        end = Some(pos);
+                        TileType::Floor
+                    }
+                    _ => TileType::from_char(char).ok_or_else(|| {
+                        format!("Unknown tile '{char}' at line {}", height as usize - y)
+                    })?,
+                };
+                row.push(TileInfo {
+                    tile_type,
+                    ..default()
+                });
+            }
+            rows.push(row);
+        }
+
+        Ok((Map(rows), start, end))
+    }
+}
+
+// Replaces map and resizes it, goals follow `MapSize`
+pub fn load_map(commands: &mut Commands, map: Map) {
+    commands.insert_resource(MapSize::new(map.width(), map.height()));
+    commands.insert_resource(map);
 }
 
 #[derive(Resource, Debug, Default, Copy, Clone)]
@@ -92,13 +218,13 @@ impl MapSize {
     }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Reflect, Copy, Clone, Debug, Default)]
 pub struct TileInfo {
     pub tile_type: TileType,
     pub color: Color,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Eq, Hash, Default)]
+#[derive(Reflect, Clone, Copy, PartialEq, Debug, Eq, Hash, Default)]
 pub enum TileType {
     #[default]
     Floor,
@@ -111,9 +237,25 @@ impl TileType {
             TileType::Wall => 1,
         }
     }
+
+    // Letters are reserved for terrain types
+    pub fn to_char(self) -> char {
+        match self {
+            TileType::Floor => '.',
+            TileType::Wall => '#',
+        }
+    }
+
+    pub fn from_char(char: char) -> Option<TileType> {
+        match char {
+            '.' => Some(TileType::Floor),
+            '#' => Some(TileType::Wall),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Component, Reflect, Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct MapPos {
     pub x: u32,
     pub y: u32,
@@ -164,14 +306,10 @@ impl MapPos {
     }
 }
 
-fn map_size_update(
-    mut commands: Commands,
-    old: Res<Map>,
-    mut old_size: Local<MapSize>,
-    new_size: Res<MapSize>,
-) {
+// Old size is taken from the map itself, it can be replaced together with `MapSize` (PNG import)
+fn map_size_update(mut commands: Commands, old: Res<Map>, new_size: Res<MapSize>) {
+    let old_size = MapSize::new(old.width(), old.height());
     commands.insert_resource(Map::new_from_old(&old, &old_size, &new_size));
-    *old_size = *new_size;
 }
 
 fn map_render(
@@ -253,3 +391,29 @@ impl Plugin for MapHandlerPlugin {
             .add_observer(map_pos_move);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_round_trip() {
+        let text = "#..G\n.#..\nS..#\n";
+        let (map, start, end) = Map::from_ascii(text).unwrap();
+
+        assert_eq!((map.width(), map.height()), (4, 3));
+        assert_eq!(start, Some(MapPos { x: 0, y: 0 }));
+        assert_eq!(end, Some(MapPos { x: 3, y: 2 }));
+        assert!(!map.is_walkable(MapPos { x: 3, y: 0 }));
+        assert_eq!(map.to_ascii(start.unwrap(), end.unwrap()), text);
+    }
+
+    #[test]
+    fn ascii_rejects_ragged_and_oversized() {
+        assert!(Map::from_ascii("...\n..\n...\n").is_err());
+        assert!(Map::from_ascii(".\n.\n").is_err());
+
+        let row = ".".repeat(MAX_MAP_SIZE as usize + 1);
+        assert!(Map::from_ascii(&format!("{row}\n{row}\n")).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use image::{ImageResult, Rgba, RgbaImage, imageops};
+
+use crate::{
+    SPRITE_SIZE,
+    api::PathOutput,
+    map::{MAX_MAP_SIZE, MIN_MAP_SIZE, Map, MapPos, TileInfo, TileType},
+};
+
+const VALID_PATH_COLOR: Rgba<u8> = Rgba([0, 200, 0, 255]);
+const INVALID_PATH_COLOR: Rgba<u8> = Rgba([200, 0, 0, 255]);
+const PATH_WIDTH: i64 = 2;
+
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+pub struct MapImageSettings {
+    // Square of pixels averaged into one tile, 16 for exported maps
+    pub pixels_per_tile: u32,
+    // Tiles darker than this are walls, 0..1
+    pub threshold: f32,
+    // Bright tiles are walls instead
+    pub invert: bool,
+    // Export timeline colors and path, not only walls and goals
+    pub overlay: bool,
+}
+impl Default for MapImageSettings {
+    fn default() -> Self {
+        MapImageSettings {
+            pixels_per_tile: 1,
+            threshold: 0.5,
+            invert: false,
+            overlay: false,
+        }
+    }
+}
+impl MapImageSettings {
+    fn tile_type(&self, pixels: &[Rgba<u8>]) -> TileType {
+        // Transparent pixels are floor
+        let (sum, count) = pixels
+            .iter()
+            .filter(|pixel| pixel.0[3] >= 128)
+            .fold((0., 0), |(sum, count), Rgba([r, g, b, _])| {
+                let luma = 0.299 * *r as f32 + 0.587 * *g as f32 + 0.114 * *b as f32;
+                (sum + luma / 255., count + 1)
+            });
+        if count * 2 < pixels.len() {
+            return TileType::Floor;
+        }
+
+        match (sum / (count as f32) < self.threshold) != self.invert {
+            true => TileType::Wall,
+            false => TileType::Floor,
+        }
+    }
+
+    // Image rows go down, map rows go up
+    pub fn load(&self, file: &Path) -> ImageResult<Map> {
+        let image = image::open(file)?.into_rgba8();
+        let size = self.pixels_per_tile.max(1);
+        let width = (image.width() / size).clamp(MIN_MAP_SIZE, MAX_MAP_SIZE);
+        let height = (image.height() / size).clamp(MIN_MAP_SIZE, MAX_MAP_SIZE);
+
+        let rows = (0..height)
+            .rev()
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let pixels: Vec<Rgba<u8>> = (0..size * size)
+                            .filter_map(|i| {
+                                image
+                                    .get_pixel_checked(x * size + i % size, y * size + i / size)
+                                    .copied()
+                            })
+                            .collect();
+                        TileInfo {
+                            tile_type: self.tile_type(&pixels),
+                            ..default()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Map(rows))
+    }
+}
+
+// Pixel of the tile center, image rows go down
+fn pixel_center(map: &Map, pos: MapPos) -> (i64, i64) {
+    let half = SPRITE_SIZE as i64 / 2;
+    (
+        (pos.x * SPRITE_SIZE) as i64 + half,
+        ((map.height() - pos.y - 1) * SPRITE_SIZE) as i64 + half,
+    )
+}
+
+fn draw_line(image: &mut RgbaImage, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: Rgba<u8>) {
+    let steps = (x1 - x0).abs().max((y1 - y0).abs()).max(1);
+
+    for step in 0..=steps {
+        let x = x0 + (x1 - x0) * step / steps;
+        let y = y0 + (y1 - y0) * step / steps;
+
+        for (dx, dy) in (0..PATH_WIDTH).flat_map(|dx| (0..PATH_WIDTH).map(move |dy| (dx, dy))) {
+            let (px, py) = (x + dx - PATH_WIDTH / 2, y + dy - PATH_WIDTH / 2);
+            if px >= 0 && py >= 0 && px < image.width() as i64 && py < image.height() as i64 {
+                image.put_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+}
+
+fn multiply(Rgba(pixel): &Rgba<u8>, tint: [u8; 4]) -> Rgba<u8> {
+    Rgba(std::array::from_fn(|c| {
+        (pixel[c] as u32 * tint[c] as u32 / 255) as u8
+    }))
+}
+
+// Software render with the same tileset as the app, works without GPU
+// Overlay is the timeline state: tile colors and validated path
+pub fn render(
+    map: &Map,
+    goals: &[(MapPos, &str)],
+    overlay: bool,
+    output: Option<&PathOutput>,
+) -> ImageResult<RgbaImage> {
+    let tileset = image::open("assets/tiles.png")?.into_rgba8();
+    let mut image = RgbaImage::new(map.width() * SPRITE_SIZE, map.height() * SPRITE_SIZE);
+
+    for (y, row) in map.0.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let tint = match overlay {
+                true => tile.color.to_srgba().to_u8_array(),
+                false => [255; 4],
+            };
+            let index = tile.tile_type.to_index() as u32;
+            let top = (map.height() - y as u32 - 1) * SPRITE_SIZE;
+
+            for py in 0..SPRITE_SIZE {
+                for px in 0..SPRITE_SIZE {
+                    let pixel = tileset.get_pixel(px, index * SPRITE_SIZE + py);
+                    image.put_pixel(x as u32 * SPRITE_SIZE + px, top + py, multiply(pixel, tint));
+                }
+            }
+        }
+    }
+
+    if let Some(output) = output.filter(|_| overlay) {
+        let color = match output.cost {
+            Ok(_) => VALID_PATH_COLOR,
+            Err(_) => INVALID_PATH_COLOR,
+        };
+        // Guests may return points outside of the map, such segments are skipped
+        let inside = |pos: &MapPos| pos.x < map.width() && pos.y < map.height();
+        for window in output.path.windows(2).filter(|window| window.iter().all(inside)) {
+            let (start, end) = (pixel_center(map, window[0]), pixel_center(map, window[1]));
+            draw_line(&mut image, start, end, color);
+        }
+    }
+
+    for (pos, sprite) in goals {
+        let sprite = image::open(format!("assets/{sprite}"))?.into_rgba8();
+        let (x, y) = pixel_center(map, *pos);
+        let half = SPRITE_SIZE as i64 / 2;
+        imageops::overlay(&mut image, &sprite, x - half, y - half);
+    }
+
+    Ok(image)
+}
+
+pub struct MapImagePlugin;
+impl Plugin for MapImagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapImageSettings>();
+    }
+}
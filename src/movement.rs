@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+
+use crate::map::{Map, MapPos};
+
+// Integer costs so guests and host agree on path costs exactly
+// Diagonal and knight moves are scaled versions of one orthogonal step
+// (10 * sqrt(2) ~= 14, 10 * sqrt(5) ~= 22)
+pub const ORTHOGONAL_COST: u32 = 10;
+pub const DIAGONAL_COST: u32 = 14;
+pub const KNIGHT_COST: u32 = 22;
+
+const ORTHOGONAL: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const EIGHT: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const KNIGHT: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+/// Which moves are allowed from a tile. Shared by host validation,
+/// statistics and the guest (passed in `run`)
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Movement {
+    #[default]
+    FourConnected,
+    EightConnected,
+    // Diagonal move is allowed only if both orthogonal tiles are floor
+    EightNoCornerCutting,
+    Knight,
+}
+impl Movement {
+    pub fn next(self) -> Movement {
+        match self {
+            Movement::FourConnected => Movement::EightConnected,
+            Movement::EightConnected => Movement::EightNoCornerCutting,
+            Movement::EightNoCornerCutting => Movement::Knight,
+            Movement::Knight => Movement::FourConnected,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Movement::FourConnected => "4-connected",
+            Movement::EightConnected => "8-connected",
+            Movement::EightNoCornerCutting => "8-connected, no corner cutting",
+            Movement::Knight => "Knight",
+        }
+    }
+
+    /// Cost of a single move by `(dx, dy)`, `None` if this movement can't do it
+    pub fn offset_cost(self, (dx, dy): (i32, i32)) -> Option<u32> {
+        match (self, dx.abs(), dy.abs()) {
+            (Movement::Knight, 1, 2) | (Movement::Knight, 2, 1) => Some(KNIGHT_COST),
+            (Movement::Knight, _, _) => None,
+            (_, 1, 0) | (_, 0, 1) => Some(ORTHOGONAL_COST),
+            (Movement::FourConnected, _, _) => None,
+            (_, 1, 1) => Some(DIAGONAL_COST),
+            _ => None,
+        }
+    }
+
+    fn offsets(self) -> impl Iterator<Item = (i32, i32)> {
+        let offsets: &[(i32, i32)] = match self {
+            Movement::FourConnected => &ORTHOGONAL,
+            Movement::EightConnected | Movement::EightNoCornerCutting => &EIGHT,
+            Movement::Knight => &KNIGHT,
+        };
+        offsets.iter().copied()
+    }
+
+    /// Cost of moving between two tiles, `None` if the move is illegal on this map
+    pub fn step_cost(self, map: &Map, from: MapPos, to: MapPos) -> Option<u32> {
+        if !map.is_walkable(from) || !map.is_walkable(to) {
+            return None;
+        }
+
+        let (dx, dy) = (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32);
+        let cost = self.offset_cost((dx, dy))?;
+
+        if self == Movement::EightNoCornerCutting
+            && dx != 0
+            && dy != 0
+            && !(map.is_walkable(MapPos { x: to.x, y: from.y })
+                && map.is_walkable(MapPos { x: from.x, y: to.y }))
+        {
+            return None;
+        }
+
+        Some(cost)
+    }
+
+    pub fn neighbors(self, map: &Map, pos: MapPos) -> Vec<(MapPos, u32)> {
+        self.offsets()
+            .filter_map(|(dx, dy)| {
+                let x = pos.x.checked_add_signed(dx)?;
+                let y = pos.y.checked_add_signed(dy)?;
+                let next = MapPos { x, y };
+
+                Some((next, self.step_cost(map, pos, next)?))
+            })
+            .collect()
+    }
+
+    /// Distance metric matching this movement on an empty map
+    /// Manhattan for 4-connected, octile for 8-connected, knight lower bound for knight
+    pub fn distance(self, from: MapPos, to: MapPos) -> u32 {
+        let dx = from.x.abs_diff(to.x);
+        let dy = from.y.abs_diff(to.y);
+
+        match self {
+            Movement::FourConnected => (dx + dy) * ORTHOGONAL_COST,
+            Movement::EightConnected | Movement::EightNoCornerCutting => {
+                dx.min(dy) * DIAGONAL_COST + dx.abs_diff(dy) * ORTHOGONAL_COST
+            }
+            Movement::Knight => {
+                dx.div_ceil(2)
+                    .max(dy.div_ceil(2))
+                    .max((dx + dy).div_ceil(3))
+                    * KNIGHT_COST
+            }
+        }
+    }
+
+    /// Total cost of a path, or description of the first broken step
+    pub fn path_cost(self, map: &Map, path: &[MapPos]) -> Result<u32, String> {
+        if let Some(pos) = path.iter().find(|pos| !map.is_walkable(**pos)) {
+            return Err(format!("({}, {}) is not walkable", pos.x, pos.y));
+        }
+
+        path.windows(2).try_fold(0, |cost, window| {
+            let (from, to) = (window[0], window[1]);
+            self.step_cost(map, from, to)
+                .map(|step| cost + step)
+                .ok_or_else(|| {
+                    format!(
+                        "({}, {}) -> ({}, {}) is not a {} move",
+                        from.x,
+                        from.y,
+                        to.x,
+                        to.y,
+                        self.name()
+                    )
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wall at (1, 0), right of the bottom left tile
+    fn corner_map() -> Map {
+        Map::from_ascii("...\n...\n.#.\n").unwrap().0
+    }
+
+    #[test]
+    fn neighbors_follow_movement() {
+        let map = Map::from_ascii("...\n...\n...\n").unwrap().0;
+        let center = MapPos { x: 1, y: 1 };
+
+        assert_eq!(Movement::FourConnected.neighbors(&map, center).len(), 4);
+        assert_eq!(Movement::EightConnected.neighbors(&map, center).len(), 8);
+        assert_eq!(
+            Movement::Knight.neighbors(&map, MapPos { x: 0, y: 0 }),
+            vec![
+                (MapPos { x: 1, y: 2 }, KNIGHT_COST),
+                (MapPos { x: 2, y: 1 }, KNIGHT_COST)
+            ]
+        );
+    }
+
+    #[test]
+    fn corner_cutting() {
+        let map = corner_map();
+        let corner = MapPos { x: 0, y: 0 };
+
+        assert_eq!(
+            Movement::EightConnected.neighbors(&map, corner),
+            vec![
+                (MapPos { x: 1, y: 1 }, DIAGONAL_COST),
+                (MapPos { x: 0, y: 1 }, ORTHOGONAL_COST)
+            ]
+        );
+        assert_eq!(
+            Movement::EightNoCornerCutting.neighbors(&map, corner),
+            vec![(MapPos { x: 0, y: 1 }, ORTHOGONAL_COST)]
+        );
+    }
+
+    #[test]
+    fn path_cost() {
+        let map = corner_map();
+        let diagonal = [MapPos { x: 0, y: 0 }, MapPos { x: 1, y: 1 }];
+        let around = [
+            MapPos { x: 0, y: 0 },
+            MapPos { x: 0, y: 1 },
+            MapPos { x: 1, y: 1 },
+        ];
+
+        assert_eq!(
+            Movement::EightConnected.path_cost(&map, &diagonal),
+            Ok(DIAGONAL_COST)
+        );
+        assert!(
+            Movement::EightNoCornerCutting
+                .path_cost(&map, &diagonal)
+                .is_err()
+        );
+        assert_eq!(
+            Movement::EightNoCornerCutting.path_cost(&map, &around),
+            Ok(2 * ORTHOGONAL_COST)
+        );
+        assert!(
+            Movement::FourConnected
+                .path_cost(&map, &[MapPos { x: 0, y: 0 }, MapPos { x: 1, y: 0 }])
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use wasmtime::{GuestProfiler, Store, WasmBacktrace, component::Component};
+
+use crate::api::WasmRunner;
+
+const INTERVAL: Duration = Duration::from_millis(1);
+// Functions shown in UI
+const HOTTEST: usize = 5;
+
+// Next run is profiled into this file
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ProfileRequest(pub Option<PathBuf>);
+
+// Samples where the function was on top of the stack
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ProfileSummary {
+    pub samples: u32,
+    pub hottest: Vec<(String, u32)>,
+}
+impl fmt::Display for ProfileSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.samples == 0 {
+            return write!(f, "No samples");
+        }
+
+        writeln!(f, "Samples: {}", self.samples)?;
+        for (name, samples) in &self.hottest {
+            writeln!(
+                f,
+                "{:.0}% {}",
+                100. * *samples as f32 / self.samples as f32,
+                name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct Sampler {
+    profiler: GuestProfiler,
+    last: Instant,
+    samples: u32,
+    functions: HashMap<String, u32>,
+}
+
+// Epoch interruption is always enabled, epoch only ticks while profiling or streaming
+pub fn configure(store: &mut Store<WasmRunner>) {
+    store.set_epoch_deadline(1);
+    store.epoch_deadline_callback(|mut store| Ok(store.data_mut().next_deadline()));
+}
+
+// Samples the guest every `INTERVAL` until `finish`
+pub struct Profile {
+    sampler: Arc<Mutex<Sampler>>,
+    running: Arc<AtomicBool>,
+    ticker: JoinHandle<()>,
+}
+impl Profile {
+    pub fn start(store: &mut Store<WasmRunner>, component: &Component, name: &str) -> Profile {
+        let sampler = Arc::new(Mutex::new(Sampler {
+            profiler: GuestProfiler::new_component(name, INTERVAL, component.clone(), []),
+            last: Instant::now(),
+            samples: 0,
+            functions: HashMap::new(),
+        }));
+
+        let callback_sampler = sampler.clone();
+        store.epoch_deadline_callback(move |mut store| {
+            let mut sampler = callback_sampler.lock().unwrap();
+            let now = Instant::now();
+            let delta = now - sampler.last;
+            sampler.last = now;
+
+            sampler.profiler.sample(&store, delta);
+            sampler.samples += 1;
+            if let Some(frame) = WasmBacktrace::capture(&store).frames().first() {
+                let name = match frame.func_name() {
+                    Some(name) => name.to_owned(),
+                    None => format!("wasm-function[{}]", frame.func_index()),
+                };
+                *sampler.functions.entry(name).or_default() += 1;
+            }
+
+            Ok(store.data_mut().next_deadline())
+        });
+        store.set_epoch_deadline(1);
+
+        let running = Arc::new(AtomicBool::new(true));
+        let ticker = {
+            let engine = store.engine().clone();
+            let running = running.clone();
+            thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    thread::sleep(INTERVAL);
+                    engine.increment_epoch();
+                }
+            })
+        };
+
+        Profile {
+            sampler,
+            running,
+            ticker,
+        }
+    }
+
+    // Writes Firefox profiler JSON to `file`
+    pub fn finish(
+        self,
+        store: &mut Store<WasmRunner>,
+        file: &Path,
+    ) -> wasmtime::Result<ProfileSummary> {
+        self.running.store(false, Ordering::Relaxed);
+        self.ticker.join().ok();
+        // Drops the sampling callback, the only other owner of `sampler`
+        configure(store);
+
+        let Some(Ok(sampler)) = Arc::into_inner(self.sampler).map(Mutex::into_inner) else {
+            return Err(wasmtime::Error::msg("Profiler is still in use"));
+        };
+        sampler.profiler.finish(File::create(file)?)?;
+        info!("Saved profile to {}", file.display());
+
+        let mut hottest: Vec<(String, u32)> = sampler.functions.into_iter().collect();
+        hottest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hottest.truncate(HOTTEST);
+
+        Ok(ProfileSummary {
+            samples: sampler.samples,
+            hottest,
+        })
+    }
+}
+
+#[derive(Component)]
+pub struct ProfileSummaryText;
+
+fn profile_summary_text_update(
+    summary: Res<ProfileSummary>,
+    mut summary_text: Single<&mut Text, With<ProfileSummaryText>>,
+) {
+    summary_text.0 = summary.to_string();
+}
+
+pub struct ProfilingPlugin;
+impl Plugin for ProfilingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProfileRequest>()
+            .init_resource::<ProfileSummary>()
+            .add_systems(
+                Update,
+                profile_summary_text_update.run_if(|summary: Res<ProfileSummary>| {
+                    summary.is_changed() && !summary.is_added()
+                }),
+            );
+    }
+}
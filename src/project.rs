@@ -0,0 +1,372 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    thread,
+    time::Instant,
+};
+
+use bevy::prelude::*;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    ui::SelectAlgorithmText,
+    wasm::{RELOAD_DEBOUNCE, WasmPathfinding, WasmState},
+};
+
+// First line is the build command, second line is the built .wasm relative to the project
+const BUILD_FILE: &str = "build.txt";
+// Default for cargo projects
+const CARGO_TARGET: &str = "wasm32-wasip2";
+// Build output shown in UI
+const OUTPUT_LINES: usize = 12;
+
+// Guest sources, rebuilt and loaded again on every save
+#[derive(Resource)]
+pub struct GuestProject {
+    dir: PathBuf,
+    command: String,
+    artifact: PathBuf,
+    _watcher: RecommendedWatcher,
+    events: Mutex<Receiver<notify::Result<notify::Event>>>,
+    // Last change of the sources, not built yet
+    changed: Option<Instant>,
+    build: Option<Build>,
+}
+impl GuestProject {
+    pub fn open(dir: PathBuf) -> Result<GuestProject, String> {
+        let (command, artifact) = build_config(&dir)?;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|err| err.to_string())?;
+        watcher
+            .watch(&dir, RecursiveMode::Recursive)
+            .map_err(|err| err.to_string())?;
+
+        info!("Watching project {}", dir.display());
+        Ok(GuestProject {
+            artifact: dir.join(artifact),
+            dir,
+            command,
+            _watcher: watcher,
+            events: Mutex::new(events),
+            changed: None,
+            build: None,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    // A build still running is killed
+    pub fn build(&mut self, output: &mut BuildOutput) {
+        self.changed = None;
+        self.build = None;
+        output.clear();
+
+        info!("Building {}: {}", self.name(), self.command);
+        match Build::start(&self.command, &self.dir) {
+            Ok(build) => {
+                output.status = format!("Building {}...", self.name());
+                self.build = Some(build);
+            }
+            Err(err) => output.status = format!("Can't run `{}`: {err}", self.command),
+        }
+    }
+
+    // Whether any received event touched the sources, build output is ignored
+    fn sources_changed(&mut self) -> bool {
+        let ignored = [self.dir.join("target"), self.artifact.clone()];
+
+        self.events
+            .get_mut()
+            .unwrap()
+            .try_iter()
+            .any(|event| match event {
+                Ok(event) => {
+                    !matches!(event.kind, EventKind::Access(_))
+                        && event.paths.iter().any(|path| {
+                            !ignored.iter().any(|ignored| path.starts_with(ignored))
+                                && !path
+                                    .strip_prefix(&self.dir)
+                                    .unwrap_or(path)
+                                    .components()
+                                    .any(|part| part.as_os_str().to_string_lossy().starts_with('.'))
+                        })
+                }
+                Err(err) => {
+                    warn!("Watcher error: {}", err);
+                    false
+                }
+            })
+    }
+}
+
+// `build.txt` in the project, or cargo defaults
+fn build_config(dir: &Path) -> Result<(String, PathBuf), String> {
+    if let Ok(config) = fs::read_to_string(dir.join(BUILD_FILE)) {
+        let mut lines = config
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        return match (lines.next(), lines.next()) {
+            (Some(command), Some(artifact)) => Ok((command.to_owned(), PathBuf::from(artifact))),
+            _ => Err(format!(
+                "{BUILD_FILE} needs a build command and a .wasm path"
+            )),
+        };
+    }
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml"))
+        .map_err(|_| format!("No {BUILD_FILE} or Cargo.toml in {}", dir.display()))?;
+    let name = cargo_package_name(&manifest).ok_or("Cargo.toml has no package name")?;
+
+    Ok((
+        format!("cargo build --release --target {CARGO_TARGET}"),
+        Path::new("target")
+            .join(CARGO_TARGET)
+            .join("release")
+            .join(format!("{}.wasm", name.replace('-', "_"))),
+    ))
+}
+
+// `name` of `[package]`, enough for guest manifests
+fn cargo_package_name(manifest: &str) -> Option<&str> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "name"
+        {
+            return Some(value.trim().trim_matches('"'));
+        }
+    }
+    None
+}
+
+// Build command with its stdout and stderr lines
+struct Build {
+    child: Child,
+    // Set once the command has exited, its output can still be coming
+    status: Option<ExitStatus>,
+    output: Mutex<Receiver<String>>,
+}
+impl Build {
+    fn start(command: &str, dir: &Path) -> io::Result<Build> {
+        let mut child = shell(command)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let (sender, output) = mpsc::channel();
+        forward(child.stdout.take(), sender.clone());
+        forward(child.stderr.take(), sender);
+
+        Ok(Build {
+            child,
+            status: None,
+            output: Mutex::new(output),
+        })
+    }
+
+    // Lines received so far, `true` once both pipes are closed
+    fn read_output(&mut self) -> (Vec<String>, bool) {
+        let output = self.output.get_mut().unwrap();
+        let mut lines = Vec::new();
+        loop {
+            match output.try_recv() {
+                Ok(line) => lines.push(line),
+                Err(TryRecvError::Empty) => return (lines, false),
+                Err(TryRecvError::Disconnected) => return (lines, true),
+            }
+        }
+    }
+}
+impl Drop for Build {
+    fn drop(&mut self) {
+        // Pid of an exited command is already reaped and can be reused
+        if self.status.is_none() {
+            kill_tree(&self.child);
+            self.child.kill().ok();
+        }
+        // No zombie is left behind
+        self.child.wait().ok();
+    }
+}
+
+// Shell and the compilers it started, the shell leads its own process group
+fn kill_tree(child: &Child) {
+    let pid = child.id().to_string();
+    let mut kill = match cfg!(windows) {
+        true => {
+            let mut kill = Command::new("taskkill");
+            kill.args(["/T", "/F", "/PID", &pid]);
+            kill
+        }
+        false => {
+            let mut kill = Command::new("kill");
+            kill.args(["-KILL", "--", &format!("-{pid}")]);
+            kill
+        }
+    };
+    if let Err(err) = kill.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+        warn!("Can't kill build {}: {}", pid, err);
+    }
+}
+
+fn shell(command: &str) -> Command {
+    let (shell, flag) = match cfg!(windows) {
+        true => ("cmd", "/C"),
+        false => ("sh", "-c"),
+    };
+    let mut shell = Command::new(shell);
+    shell.args([flag, command]);
+    // Killed together with everything it starts, see `kill_tree`
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+    shell
+}
+
+// Reader ends when the pipe is closed, dropping its sender
+fn forward(pipe: Option<impl Read + Send + 'static>, sender: Sender<String>) {
+    thread::spawn(move || {
+        let Some(pipe) = pipe else {
+            return;
+        };
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct BuildOutput {
+    pub status: String,
+    // Output of the last build
+    pub lines: VecDeque<String>,
+}
+impl BuildOutput {
+    fn clear(&mut self) {
+        self.status.clear();
+        self.lines.clear();
+    }
+}
+
+// Builds after the sources have been quiet for `RELOAD_DEBOUNCE`
+fn project_watch(mut project: ResMut<GuestProject>, mut output: ResMut<BuildOutput>) {
+    let now = Instant::now();
+    if project.sources_changed() {
+        project.changed = Some(now);
+    }
+
+    if let Some(changed) = project.changed
+        && now - changed >= RELOAD_DEBOUNCE
+    {
+        project.build(&mut output);
+    }
+}
+
+// Streams build output, a successful build replaces the algorithm and runs it again
+fn project_build_poll(
+    mut commands: Commands,
+    mut project: ResMut<GuestProject>,
+    mut output: ResMut<BuildOutput>,
+    mut algorithm_text: Single<&mut Text, With<SelectAlgorithmText>>,
+    mut mut_state: ResMut<NextState<WasmState>>,
+) {
+    let Some(build) = project.build.as_mut() else {
+        return;
+    };
+
+    if build.status.is_none() {
+        match build.child.try_wait() {
+            Ok(status) => build.status = status,
+            Err(err) => {
+                output.status = format!("Build failed: {err}");
+                project.build = None;
+                return;
+            }
+        }
+    }
+
+    let (lines, closed) = build.read_output();
+    for line in lines {
+        output.lines.push_back(line);
+        if output.lines.len() > OUTPUT_LINES {
+            output.lines.pop_front();
+        }
+    }
+
+    // Last lines can arrive after the command has exited
+    let Some(status) = build.status.filter(|_| closed) else {
+        return;
+    };
+    project.build = None;
+
+    if !status.success() {
+        error!("Build of {} failed: {}", project.name(), status);
+        output.status = format!("Build failed: {status}");
+        return;
+    }
+
+    match WasmPathfinding::load(&project.artifact) {
+        Ok(wasm) => {
+            output.status = format!("Built {}", project.name());
+            algorithm_text.0 = wasm.name();
+            commands.insert_resource(wasm);
+            mut_state.set(WasmState::Run);
+        }
+        Err(err) => {
+            error!("{}", err);
+            output.status = format!("Can't load {}: {err}", project.artifact.display());
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BuildOutputText;
+
+fn build_output_text_update(
+    output: Res<BuildOutput>,
+    mut output_text: Single<&mut Text, With<BuildOutputText>>,
+) {
+    output_text.0 = output.status.clone();
+    for line in &output.lines {
+        output_text.0.push('\n');
+        output_text.0.push_str(line);
+    }
+}
+
+pub struct GuestProjectPlugin;
+impl Plugin for GuestProjectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildOutput>()
+            .add_systems(
+                Update,
+                (project_watch, project_build_poll)
+                    .chain()
+                    .run_if(resource_exists::<GuestProject>),
+            )
+            .add_systems(
+                Update,
+                build_output_text_update.run_if(|output: Res<BuildOutput>| output.is_changed()),
+            );
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    SPRITE_SIZE,
+    goals::{Flag, Fox},
+    map::{Map, MapPos},
+    movement::Movement,
+    wasm::tile_center,
+};
+
+// Between tilemap (z = 0) and guest shapes
+const OVERLAY_Z: f32 = 0.05;
+const OVERLAY_ALPHA: f32 = 0.35;
+// Golden angle, neighbouring components get distant hues
+const HUE_STEP: f32 = 137.5;
+
+// Connected components of floor tiles under the active movement,
+// every movement is symmetric so components are undirected
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct Reachability {
+    width: u32,
+    // Row-major from (0, 0), `None` for walls
+    components: Vec<Option<u32>>,
+    pub count: u32,
+}
+impl Reachability {
+    pub fn new(map: &Map, movement: Movement) -> Reachability {
+        let (width, height) = (map.width(), map.height());
+        let mut reachability = Reachability {
+            width,
+            components: vec![None; (width * height) as usize],
+            count: 0,
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let pos = MapPos { x, y };
+                if !map.is_walkable(pos) || reachability.component(pos).is_some() {
+                    continue;
+                }
+
+                let component = reachability.count;
+                reachability.count += 1;
+                reachability.set(pos, component);
+
+                let mut open = VecDeque::from([pos]);
+                while let Some(pos) = open.pop_front() {
+                    for (next, _) in movement.neighbors(map, pos) {
+                        if reachability.component(next).is_none() {
+                            reachability.set(next, component);
+                            open.push_back(next);
+                        }
+                    }
+                }
+            }
+        }
+        reachability
+    }
+
+    fn index(&self, pos: MapPos) -> Option<usize> {
+        (pos.x < self.width).then(|| (pos.y * self.width + pos.x) as usize)
+    }
+
+    fn set(&mut self, pos: MapPos, component: u32) {
+        if let Some(slot) = self
+            .index(pos)
+            .and_then(|index| self.components.get_mut(index))
+        {
+            *slot = Some(component);
+        }
+    }
+
+    // `None` for walls and tiles outside of the map
+    pub fn component(&self, pos: MapPos) -> Option<u32> {
+        self.components.get(self.index(pos)?).copied().flatten()
+    }
+
+    pub fn connected(&self, from: MapPos, to: MapPos) -> bool {
+        self.component(from)
+            .is_some_and(|component| self.component(to) == Some(component))
+    }
+}
+
+// Colors components on the map
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+pub struct ReachabilityOverlay(pub bool);
+
+#[derive(Component)]
+struct ReachabilityTile;
+
+fn reachability_update(
+    map: Res<Map>,
+    movement: Res<Movement>,
+    mut reachability: ResMut<Reachability>,
+) {
+    // Map also changes when it is recolored, overlay is rebuilt only for new components
+    reachability.set_if_neq(Reachability::new(&map, *movement));
+}
+
+fn reachability_overlay(
+    mut commands: Commands,
+    reachability: Res<Reachability>,
+    overlay: Res<ReachabilityOverlay>,
+    tiles: Query<Entity, With<ReachabilityTile>>,
+) {
+    tiles
+        .iter()
+        .for_each(|tile| commands.entity(tile).despawn());
+    if !overlay.0 {
+        return;
+    }
+
+    for (index, component) in reachability.components.iter().enumerate() {
+        let Some(component) = component else {
+            continue;
+        };
+        let pos = MapPos {
+            x: index as u32 % reachability.width,
+            y: index as u32 / reachability.width,
+        };
+        let color = Color::hsla(
+            (*component as f32 * HUE_STEP) % 360.,
+            0.8,
+            0.5,
+            OVERLAY_ALPHA,
+        );
+
+        commands.spawn((
+            Sprite::from_color(color, Vec2::splat(SPRITE_SIZE as f32)),
+            Transform::from_translation(tile_center(pos).extend(OVERLAY_Z)),
+            ReachabilityTile,
+        ));
+    }
+}
+
+#[derive(Component)]
+pub struct ReachabilityText;
+
+fn reachability_text_update(
+    reachability: Res<Reachability>,
+    fox: Single<&MapPos, With<Fox>>,
+    flag: Single<&MapPos, With<Flag>>,
+    mut reachability_text: Single<&mut Text, With<ReachabilityText>>,
+) {
+    reachability_text.0 = match reachability.connected(**fox, **flag) {
+        true => format!("Areas: {}", reachability.count),
+        false => format!(
+            "Areas: {}\nWarning: flag is unreachable",
+            reachability.count
+        ),
+    };
+}
+
+pub struct ReachabilityPlugin;
+impl Plugin for ReachabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Reachability>()
+            .init_resource::<ReachabilityOverlay>()
+            .add_systems(
+                Update,
+                (
+                    reachability_update.run_if(|map: Res<Map>, movement: Res<Movement>| {
+                        map.is_changed() || movement.is_changed()
+                    }),
+                    reachability_overlay.run_if(
+                        |reachability: Res<Reachability>, overlay: Res<ReachabilityOverlay>| {
+                            reachability.is_changed() || overlay.is_changed()
+                        },
+                    ),
+                    reachability_text_update.run_if(
+                        |reachability: Res<Reachability>,
+                         goals: Query<(), (Changed<MapPos>, Or<(With<Fox>, With<Flag>)>)>| {
+                            reachability.is_changed() || !goals.is_empty()
+                        },
+                    ),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components_split_by_walls() {
+        let (map, _, _) = Map::from_ascii(".#.\n.#.\n.#.\n").unwrap();
+        let (left, right) = (MapPos { x: 0, y: 0 }, MapPos { x: 2, y: 0 });
+
+        let four = Reachability::new(&map, Movement::FourConnected);
+        assert_eq!(four.count, 2);
+        assert!(four.connected(left, MapPos { x: 0, y: 2 }));
+        assert!(!four.connected(left, right));
+        assert_eq!(four.component(MapPos { x: 1, y: 1 }), None);
+        assert_eq!(four.component(MapPos { x: 3, y: 0 }), None);
+
+        // Knight jumps over the wall, but only reaches every other tile
+        let knight = Reachability::new(&map, Movement::Knight);
+        assert_eq!(knight.count, 2);
+        assert!(knight.connected(left, MapPos { x: 2, y: 1 }));
+        assert!(!knight.connected(left, right));
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    fmt,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    api::{PathOutput, RunInput},
+    goals::{Flag, Fox},
+    map::{Map, MapPos},
+    movement::{DIAGONAL_COST, Movement, ORTHOGONAL_COST},
+    statistics::Statistics,
+    wasm::tile_center,
+};
+
+const OVERLAY_COLOR: Color = Color::srgb(0.9, 0.2, 0.9);
+
+// Estimates of the remaining cost for A*, only `Movement` is admissible for every movement
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heuristic {
+    #[default]
+    Movement,
+    Manhattan,
+    Octile,
+    Chebyshev,
+    Euclidean,
+}
+impl Heuristic {
+    fn next(self) -> Option<Heuristic> {
+        match self {
+            Heuristic::Movement => Some(Heuristic::Manhattan),
+            Heuristic::Manhattan => Some(Heuristic::Octile),
+            Heuristic::Octile => Some(Heuristic::Chebyshev),
+            Heuristic::Chebyshev => Some(Heuristic::Euclidean),
+            Heuristic::Euclidean => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heuristic::Movement => "movement",
+            Heuristic::Manhattan => "manhattan",
+            Heuristic::Octile => "octile",
+            Heuristic::Chebyshev => "chebyshev",
+            Heuristic::Euclidean => "euclidean",
+        }
+    }
+
+    // In movement cost units, one orthogonal step is `ORTHOGONAL_COST`
+    fn estimate(self, movement: Movement, from: MapPos, to: MapPos) -> u32 {
+        let dx = from.x.abs_diff(to.x);
+        let dy = from.y.abs_diff(to.y);
+
+        match self {
+            Heuristic::Movement => movement.distance(from, to),
+            Heuristic::Manhattan => (dx + dy) * ORTHOGONAL_COST,
+            Heuristic::Octile => dx.min(dy) * DIAGONAL_COST + dx.abs_diff(dy) * ORTHOGONAL_COST,
+            Heuristic::Chebyshev => dx.max(dy) * ORTHOGONAL_COST,
+            Heuristic::Euclidean => {
+                (((dx * dx + dy * dy) as f32).sqrt() * ORTHOGONAL_COST as f32) as u32
+            }
+        }
+    }
+}
+
+// Native solvers, using the same movement rules and costs as validation
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReferenceSolver {
+    // Fewest moves, optimal only when all moves cost the same
+    Bfs,
+    #[default]
+    Dijkstra,
+    AStar(Heuristic),
+}
+impl ReferenceSolver {
+    pub fn next(self) -> ReferenceSolver {
+        match self {
+            ReferenceSolver::Bfs => ReferenceSolver::Dijkstra,
+            ReferenceSolver::Dijkstra => ReferenceSolver::AStar(Heuristic::default()),
+            ReferenceSolver::AStar(heuristic) => match heuristic.next() {
+                Some(heuristic) => ReferenceSolver::AStar(heuristic),
+                None => ReferenceSolver::Bfs,
+            },
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            ReferenceSolver::Bfs => "BFS".to_owned(),
+            ReferenceSolver::Dijkstra => "Dijkstra".to_owned(),
+            ReferenceSolver::AStar(heuristic) => format!("A* ({})", heuristic.name()),
+        }
+    }
+
+    // `None` if the flag can't be reached
+    pub fn solve(self, input: &RunInput) -> Option<ReferencePath> {
+        let RunInput { movement, end, .. } = *input;
+
+        match self {
+            ReferenceSolver::Bfs => bfs(input),
+            ReferenceSolver::Dijkstra => best_first(input, |_| 0),
+            ReferenceSolver::AStar(heuristic) => {
+                best_first(input, |pos| heuristic.estimate(movement, pos, end))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferencePath {
+    pub path: Vec<MapPos>,
+    pub cost: u32,
+    pub expanded: u32,
+    pub max_open: usize,
+}
+
+// Ground truth for validation
+pub fn optimal_cost(input: &RunInput) -> Option<u32> {
+    ReferenceSolver::Dijkstra.solve(input).map(|path| path.cost)
+}
+
+fn walk_back(parents: &HashMap<MapPos, MapPos>, end: MapPos) -> Vec<MapPos> {
+    let mut path = vec![end];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(*parent);
+    }
+    path.reverse();
+    path
+}
+
+fn bfs(input: &RunInput) -> Option<ReferencePath> {
+    let RunInput {
+        map,
+        movement,
+        start,
+        end,
+    } = input;
+    if !map.is_walkable(*start) || !map.is_walkable(*end) {
+        return None;
+    }
+
+    let mut parents = HashMap::new();
+    let mut open = VecDeque::from([*start]);
+    let mut max_open = 1;
+    let mut expanded = 0;
+
+    while let Some(pos) = open.pop_front() {
+        expanded += 1;
+        if pos == *end {
+            let path = walk_back(&parents, pos);
+            return Some(ReferencePath {
+                cost: movement.path_cost(map, &path).ok()?,
+                path,
+                expanded,
+                max_open,
+            });
+        }
+
+        for (next, _) in movement.neighbors(map, pos) {
+            if next != *start && !parents.contains_key(&next) {
+                parents.insert(next, pos);
+                open.push_back(next);
+            }
+        }
+        max_open = max_open.max(open.len());
+    }
+    None
+}
+
+// Dijkstra with zero `estimate`, A* otherwise
+fn best_first(input: &RunInput, estimate: impl Fn(MapPos) -> u32) -> Option<ReferencePath> {
+    let RunInput {
+        map,
+        movement,
+        start,
+        end,
+    } = input;
+    if !map.is_walkable(*start) || !map.is_walkable(*end) {
+        return None;
+    }
+
+    let mut costs = HashMap::from([(*start, 0)]);
+    let mut parents = HashMap::new();
+    // Ties are broken by the larger cost so far, closer to the flag
+    let mut open = BinaryHeap::from([(Reverse(estimate(*start)), 0, <(u32, u32)>::from(*start))]);
+    let mut max_open = 1;
+    let mut expanded = 0;
+
+    while let Some((_, cost, pos)) = open.pop() {
+        let pos = MapPos::from(pos);
+        if costs.get(&pos).is_some_and(|best| cost > *best) {
+            continue;
+        }
+
+        expanded += 1;
+        if pos == *end {
+            return Some(ReferencePath {
+                path: walk_back(&parents, pos),
+                cost,
+                expanded,
+                max_open,
+            });
+        }
+
+        for (next, step) in movement.neighbors(map, pos) {
+            let next_cost = cost + step;
+            if costs.get(&next).is_none_or(|best| next_cost < *best) {
+                costs.insert(next, next_cost);
+                parents.insert(next, pos);
+                open.push((Reverse(next_cost + estimate(next)), next_cost, next.into()));
+            }
+        }
+        max_open = max_open.max(open.len());
+    }
+    None
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+pub struct ReferenceSettings {
+    pub solver: ReferenceSolver,
+    // Draws the reference path over the guest drawing
+    pub overlay: bool,
+}
+
+// Reference run of the current map and goals
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct ReferenceResult {
+    pub solver: ReferenceSolver,
+    pub path: Option<ReferencePath>,
+}
+impl fmt::Display for ReferenceResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(
+                f,
+                "{}: cost {}, {} expanded",
+                self.solver.name(),
+                path.cost,
+                path.expanded
+            ),
+            None => write!(f, "{}: no path", self.solver.name()),
+        }
+    }
+}
+
+fn current_input(
+    map: &Map,
+    movement: Movement,
+    fox: &Query<&MapPos, With<Fox>>,
+    flag: &Query<&MapPos, With<Flag>>,
+) -> Option<RunInput> {
+    Some(RunInput {
+        map: map.clone(),
+        movement,
+        start: *fox.single().ok()?,
+        end: *flag.single().ok()?,
+    })
+}
+
+// Recomputed whenever the scenario changes while the overlay is shown
+fn reference_update(
+    settings: Res<ReferenceSettings>,
+    map: Res<Map>,
+    movement: Res<Movement>,
+    fox: Query<&MapPos, With<Fox>>,
+    flag: Query<&MapPos, With<Flag>>,
+    mut result: ResMut<ReferenceResult>,
+) {
+    let Some(input) = current_input(&map, *movement, &fox, &flag) else {
+        return;
+    };
+    // Map also changes when it is recolored, text is updated only for new results
+    result.set_if_neq(ReferenceResult {
+        solver: settings.solver,
+        path: settings.solver.solve(&input),
+    });
+}
+
+// Runs the reference solver instead of the guest, its path shows up like a guest path
+pub fn reference_run(
+    mut settings: ResMut<ReferenceSettings>,
+    map: Res<Map>,
+    movement: Res<Movement>,
+    fox: Query<&MapPos, With<Fox>>,
+    flag: Query<&MapPos, With<Flag>>,
+    mut statistics: ResMut<Statistics>,
+) {
+    let Some(input) = current_input(&map, *movement, &fox, &flag) else {
+        return;
+    };
+    settings.overlay = true;
+
+    *statistics = Statistics::new(input.movement, input.start, input.end);
+    if let Some(path) = settings.solver.solve(&input) {
+        statistics.expanded = path.expanded;
+        statistics.max_open = path.max_open;
+        statistics.output = Some(PathOutput {
+            cost: Ok(path.cost),
+            optimal: optimal_cost(&input),
+            path: path.path,
+            no_path: false,
+        });
+    }
+}
+
+fn reference_overlay(
+    mut gizmos: Gizmos,
+    settings: Res<ReferenceSettings>,
+    result: Res<ReferenceResult>,
+) {
+    if let Some(path) = result.path.as_ref().filter(|_| settings.overlay) {
+        gizmos.linestrip_2d(path.path.iter().copied().map(tile_center), OVERLAY_COLOR);
+    }
+}
+
+#[derive(Component)]
+pub struct ReferenceText;
+
+fn reference_text_update(
+    settings: Res<ReferenceSettings>,
+    result: Res<ReferenceResult>,
+    mut reference_text: Single<&mut Text, With<ReferenceText>>,
+) {
+    reference_text.0 = match settings.overlay {
+        true => result.to_string(),
+        false => format!("Reference: {}", settings.solver.name()),
+    };
+}
+
+pub struct ReferencePlugin;
+impl Plugin for ReferencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReferenceSettings>()
+            .init_resource::<ReferenceResult>()
+            .add_systems(
+                Update,
+                (
+                    reference_update.run_if(
+                        |settings: Res<ReferenceSettings>,
+                         map: Res<Map>,
+                         movement: Res<Movement>,
+                         goals: Query<(), (Changed<MapPos>, Or<(With<Fox>, With<Flag>)>)>| {
+                            settings.overlay
+                                && (settings.is_changed()
+                                    || map.is_changed()
+                                    || movement.is_changed()
+                                    || !goals.is_empty())
+                        },
+                    ),
+                    reference_overlay,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                reference_text_update.run_if(
+                    |settings: Res<ReferenceSettings>, result: Res<ReferenceResult>| {
+                        settings.is_changed() || result.is_changed()
+                    },
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPS: [&str; 3] = [
+        "S...\n.##.\n...G\n",
+        "S.#..\n..#..\n.....\n.#.#G\n",
+        "S#...\n.#.#.\n.#.#.\n...#G\n",
+    ];
+    const MOVEMENTS: [Movement; 4] = [
+        Movement::FourConnected,
+        Movement::EightConnected,
+        Movement::EightNoCornerCutting,
+        Movement::Knight,
+    ];
+
+    fn input(text: &str, movement: Movement) -> RunInput {
+        let (map, start, end) = Map::from_ascii(text).unwrap();
+        RunInput {
+            map,
+            movement,
+            start: start.unwrap(),
+            end: end.unwrap(),
+        }
+    }
+
+    #[test]
+    fn dijkstra_and_a_star_agree() {
+        for text in MAPS {
+            for movement in MOVEMENTS {
+                let input = input(text, movement);
+                let optimal = optimal_cost(&input);
+                let a_star = ReferenceSolver::AStar(Heuristic::Movement).solve(&input);
+
+                assert_eq!(a_star.as_ref().map(|path| path.cost), optimal);
+                if let Some(path) = a_star {
+                    assert_eq!(movement.path_cost(&input.map, &path.path), Ok(path.cost));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn known_optimal_costs() {
+        let four = input(MAPS[0], Movement::FourConnected);
+        assert_eq!(optimal_cost(&four), Some(5 * ORTHOGONAL_COST));
+        // Every move costs the same, so BFS is optimal too
+        assert_eq!(
+            ReferenceSolver::Bfs.solve(&four).map(|path| path.cost),
+            Some(5 * ORTHOGONAL_COST)
+        );
+
+        let walled = input("S#.\n##.\n..G\n", Movement::EightConnected);
+        assert_eq!(optimal_cost(&walled), None);
+    }
+}
@@ -0,0 +1,150 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+
+use crate::map::{MAX_MAP_SIZE, MIN_MAP_SIZE, Map, MapPos, TileInfo, TileType};
+
+// First byte of every code, stays uncompressed so the format can change later
+// Old versions must keep decoding
+const VERSION: u8 = 1;
+const COMPRESSION_LEVEL: u8 = 9;
+// Header and one bit per tile of the biggest map
+const MAX_PAYLOAD: usize = 6 + (MAX_MAP_SIZE * MAX_MAP_SIZE).div_ceil(8) as usize;
+
+// Everything needed to reconstruct a scene
+#[derive(Debug, Clone)]
+pub struct SharedMap {
+    pub map: Map,
+    pub start: MapPos,
+    pub end: MapPos,
+}
+impl SharedMap {
+    // Version 1 payload, deflated:
+    // width, height, start x, start y, end x, end y, then walls as bits row-major from (0, 0)
+    pub fn encode(&self) -> String {
+        let mut payload = vec![
+            self.map.width() as u8,
+            self.map.height() as u8,
+            self.start.x as u8,
+            self.start.y as u8,
+            self.end.x as u8,
+            self.end.y as u8,
+        ];
+
+        let tiles: Vec<&TileInfo> = self.map.0.iter().flatten().collect();
+        payload.extend(tiles.chunks(8).map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, tile)| tile.tile_type == TileType::Wall)
+                .fold(0u8, |byte, (bit, _)| byte | (1 << bit))
+        }));
+
+        let mut code = vec![VERSION];
+        code.extend(compress_to_vec(&payload, COMPRESSION_LEVEL));
+        URL_SAFE_NO_PAD.encode(code)
+    }
+
+    pub fn decode(code: &str) -> Result<SharedMap, String> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(code.trim())
+            .map_err(|err| format!("Not a map code: {err}"))?;
+
+        match bytes.split_first() {
+            Some((&VERSION, data)) => decode_v1(data),
+            Some((version, _)) => Err(format!("Unknown map code version {version}")),
+            None => Err("Map code is empty".to_owned()),
+        }
+    }
+}
+
+fn decode_v1(data: &[u8]) -> Result<SharedMap, String> {
+    let payload = decompress_to_vec_with_limit(data, MAX_PAYLOAD)
+        .map_err(|err| format!("Corrupted map code: {err:?}"))?;
+
+    let Some((&[width, height, start_x, start_y, end_x, end_y], bits)) =
+        payload.split_first_chunk()
+    else {
+        return Err("Map code is too short".to_owned());
+    };
+    let (width, height) = (width as u32, height as u32);
+
+    if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&width)
+        || !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&height)
+    {
+        return Err(format!("Map code has invalid size {width}x{height}"));
+    }
+    if bits.len() != (width * height).div_ceil(8) as usize {
+        return Err("Map code has wrong number of tiles".to_owned());
+    }
+
+    let start = MapPos {
+        x: start_x as u32,
+        y: start_y as u32,
+    };
+    let end = MapPos {
+        x: end_x as u32,
+        y: end_y as u32,
+    };
+    if [start, end]
+        .iter()
+        .any(|pos| pos.x >= width || pos.y >= height)
+        || start == end
+    {
+        return Err("Map code has invalid goals".to_owned());
+    }
+
+    let rows = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    let index = (y * width + x) as usize;
+                    let tile_type = match (bits[index / 8] >> (index % 8)) & 1 {
+                        1 => TileType::Wall,
+                        _ => TileType::Floor,
+                    };
+                    TileInfo {
+                        tile_type,
+                        ..Default::default()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(SharedMap {
+        map: Map(rows),
+        start,
+        end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Code made by version 1, must keep decoding
+    const V1_CODE: &str = "AWNhZmBgZtJgBAA";
+
+    #[test]
+    fn decodes_v1_code() {
+        let shared = SharedMap::decode(V1_CODE).unwrap();
+
+        assert_eq!(shared.start, MapPos { x: 0, y: 0 });
+        assert_eq!(shared.end, MapPos { x: 3, y: 2 });
+        assert_eq!(
+            shared.map.to_ascii(shared.start, shared.end),
+            "#..G\n.#..\nS..#\n"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let shared = SharedMap::decode(V1_CODE).unwrap();
+        let decoded = SharedMap::decode(&shared.encode()).unwrap();
+
+        assert_eq!(
+            decoded.map.to_ascii(decoded.start, decoded.end),
+            shared.map.to_ascii(shared.start, shared.end)
+        );
+    }
+}
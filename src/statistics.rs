@@ -0,0 +1,69 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::{api::PathOutput, map::MapPos, movement::Movement};
+
+// Summary of the last run, shown in UI
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+pub struct Statistics {
+    pub movement: Movement,
+    // Fox -> flag on an empty map, using metric of `movement`
+    pub distance: u32,
+    pub output: Option<PathOutput>,
+    // Unique nodes passed to graph queries and total amount of queries
+    pub queried_nodes: usize,
+    pub queries: u32,
+    // From search events
+    pub expanded: u32,
+    pub max_open: usize,
+}
+impl Statistics {
+    pub fn new(movement: Movement, start: MapPos, end: MapPos) -> Self {
+        Statistics {
+            movement,
+            distance: movement.distance(start, end),
+            ..default()
+        }
+    }
+}
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Movement: {}", self.movement.name())?;
+        writeln!(f, "Distance: {}", self.distance)?;
+        writeln!(
+            f,
+            "Explored: {} nodes ({} queries)",
+            self.queried_nodes, self.queries
+        )?;
+        writeln!(
+            f,
+            "Expanded: {} (max open: {})",
+            self.expanded, self.max_open
+        )?;
+
+        match &self.output {
+            None => write!(f, "Path: none"),
+            Some(output) if output.is_no_path() => {
+                writeln!(f, "Path: none reported")?;
+                match &output.cost {
+                    Ok(_) => write!(f, "Correct: flag is unreachable"),
+                    Err(err) => write!(f, "Invalid: {err}"),
+                }
+            }
+            Some(output) => {
+                writeln!(f, "Path: {} tiles", output.path.len())?;
+                match &output.cost {
+                    Ok(cost) => match output.optimal {
+                        Some(optimal) if *cost > optimal => {
+                            write!(f, "Cost: {cost} (suboptimal, optimal {optimal})")
+                        }
+                        Some(_) => write!(f, "Cost: {cost} (optimal)"),
+                        None => write!(f, "Cost: {cost}"),
+                    },
+                    Err(err) => write!(f, "Invalid: {err}"),
+                }
+            }
+        }
+    }
+}
@@ -1,21 +1,41 @@
 use core::time::Duration;
 use std::env::current_dir;
 
+use arboard::Clipboard;
 use bevy::{
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    input::mouse::{MouseScrollUnit, MouseWheel},
     math::Vec2,
     prelude::*,
     time::common_conditions::on_timer,
-    ui_widgets::{Activate, UiWidgetsPlugins, ValueChange, observe},
+    ui::{Checked, RelativeCursorPosition},
+    ui_widgets::{Activate, SliderValue, UiWidgetsPlugins, ValueChange, observe},
 };
 use rfd::FileDialog;
 
 use crate::{
     SPRITE_SIZE,
     components::*,
-    goals::Fox,
-    map::{Map, MapSize},
-    wasm::{WasmHotReloading, WasmPathfinding, WasmState},
+    crowd::{Crowd, CrowdText},
+    debugger::{BreakpointText, Breakpoints},
+    fuzz::{FuzzText, Fuzzing},
+    goals::{Flag, Fox},
+    heatmap::{Colormap, HeatmapLegend},
+    history::{RunHistory, RunHistoryList},
+    labels::{LabelMode, TileLabels},
+    layers::LayersList,
+    map::{self, MAX_MAP_SIZE, MIN_MAP_SIZE, Map, MapPos, MapSize},
+    map_image::{self, MapImageSettings},
+    movement::Movement,
+    profiling::{ProfileRequest, ProfileSummaryText},
+    project::{BuildOutput, BuildOutputText, GuestProject},
+    reachability::{ReachabilityOverlay, ReachabilityText},
+    reference::{ReferenceSettings, ReferenceText, reference_run},
+    share::SharedMap,
+    statistics::Statistics,
+    walk::FoxWalk,
+    wasm::{WasmHotReloading, WasmPathfinding, WasmState, WasmStreaming},
+    workspace::{workspace_open, workspace_save},
 };
 
 fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
@@ -29,8 +49,12 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
             row_gap: px(8),
             flex_direction: FlexDirection::Column,
             align_items: AlignItems::Start,
+            overflow: Overflow::scroll_y(),
             ..default()
         },
+        ScrollPosition::default(),
+        RelativeCursorPosition::default(),
+        SidePanel,
         BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
         BorderColor {
             right: Color::BLACK,
@@ -39,6 +63,28 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
         children![
             (text("FPS: ?", 32.), FPSText),
             separator(),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        button(text("Save workspace", 24.)),
+                        observe(|_: On<Activate>, mut commands: Commands| {
+                            commands.run_system_cached(workspace_save);
+                        }),
+                    ),
+                    (
+                        button(text("Open workspace", 24.)),
+                        observe(|_: On<Activate>, mut commands: Commands| {
+                            commands.run_system_cached(workspace_open);
+                        }),
+                    ),
+                ],
+            ),
             (
                 button(text("Center camera", 24.)),
                 observe(
@@ -59,6 +105,124 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
                     }
                 )
             ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        button(text("Copy map", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             map: Res<Map>,
+                             fox: Single<&MapPos, With<Fox>>,
+                             flag: Single<&MapPos, With<Flag>>| {
+                                if let Err(err) = Clipboard::new().and_then(|mut clipboard| {
+                                    clipboard.set_text(map.to_ascii(**fox, **flag))
+                                }) {
+                                    error!("{}", err);
+                                }
+                            }
+                        ),
+                    ),
+                    (
+                        button(text("Paste map", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             mut commands: Commands,
+                             fox: Single<Entity, With<Fox>>,
+                             flag: Single<Entity, With<Flag>>| {
+                                let text = match Clipboard::new()
+                                    .and_then(|mut clipboard| clipboard.get_text())
+                                {
+                                    Ok(text) => text,
+                                    Err(err) => {
+                                        error!("{}", err);
+                                        return;
+                                    }
+                                };
+
+                                match Map::from_ascii(&text) {
+                                    Ok((new_map, start, end)) => {
+                                        map::load_map(&mut commands, new_map);
+                                        if let Some(start) = start {
+                                            commands.entity(*fox).insert(start);
+                                        }
+                                        if let Some(end) = end {
+                                            commands.entity(*flag).insert(end);
+                                        }
+                                    }
+                                    Err(err) => error!("Can't paste map: {}", err),
+                                }
+                            }
+                        ),
+                    ),
+                ],
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        button(text("Copy code", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             map: Res<Map>,
+                             fox: Single<&MapPos, With<Fox>>,
+                             flag: Single<&MapPos, With<Flag>>| {
+                                let code = SharedMap {
+                                    map: map.clone(),
+                                    start: **fox,
+                                    end: **flag,
+                                }
+                                .encode();
+                                info!("Map code: {}", code);
+
+                                if let Err(err) = Clipboard::new()
+                                    .and_then(|mut clipboard| clipboard.set_text(code))
+                                {
+                                    error!("{}", err);
+                                }
+                            }
+                        ),
+                    ),
+                    (
+                        button(text("Paste code", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             mut commands: Commands,
+                             fox: Single<Entity, With<Fox>>,
+                             flag: Single<Entity, With<Flag>>| {
+                                let code = match Clipboard::new()
+                                    .and_then(|mut clipboard| clipboard.get_text())
+                                {
+                                    Ok(code) => code,
+                                    Err(err) => {
+                                        error!("{}", err);
+                                        return;
+                                    }
+                                };
+
+                                match SharedMap::decode(&code) {
+                                    Ok(shared) => {
+                                        map::load_map(&mut commands, shared.map);
+                                        commands.entity(*fox).insert(shared.start);
+                                        commands.entity(*flag).insert(shared.end);
+                                    }
+                                    Err(err) => error!("Can't paste map code: {}", err),
+                                }
+                            }
+                        ),
+                    ),
+                ],
+            ),
             separator(),
             (text("...", 24.), SelectAlgorithmText),
             (
@@ -76,14 +240,13 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
                         {
                             match WasmPathfinding::load(&file) {
                                 Ok(wasm) => {
+                                    text.0 = wasm.name();
                                     commands.insert_resource(wasm);
-                                    text.0 =
-                                        format!("{}", file.file_name().unwrap().to_string_lossy());
                                     mut_state.set(WasmState::Run);
                                 }
                                 Err(err) => {
                                     error!("{}", err);
-                                    text.0 = "Error loading wasm".to_owned();
+                                    text.0 = format!("Error loading wasm: {err}");
                                 }
                             }
                         }
@@ -100,6 +263,7 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
                 children![
                     (
                         checkbox(),
+                        SettingCheckbox::HotReloading,
                         observe(
                             |event: On<ValueChange<bool>>, mut state: ResMut<WasmHotReloading>| {
                                 state.0 = event.value;
@@ -109,6 +273,337 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
                     text("Hot-reloading", 24.),
                 ],
             ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        button(text("Select project", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             mut commands: Commands,
+                             mut output: ResMut<BuildOutput>| {
+                                if let Some(dir) = FileDialog::new()
+                                    .set_directory(current_dir().unwrap().join("algorithms"))
+                                    .pick_folder()
+                                {
+                                    match GuestProject::open(dir) {
+                                        Ok(mut project) => {
+                                            project.build(&mut output);
+                                            commands.insert_resource(project);
+                                        }
+                                        Err(err) => {
+                                            error!("{}", err);
+                                            output.status = format!("Can't open project: {err}");
+                                        }
+                                    }
+                                }
+                            }
+                        ),
+                    ),
+                    (
+                        button(text("Build", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             project: Option<ResMut<GuestProject>>,
+                             mut output: ResMut<BuildOutput>| {
+                                if let Some(mut project) = project {
+                                    project.build(&mut output);
+                                }
+                            }
+                        ),
+                    ),
+                ],
+            ),
+            (text("", 20.), BuildOutputText),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        checkbox(),
+                        SettingCheckbox::Streaming,
+                        observe(
+                            |event: On<ValueChange<bool>>, mut streaming: ResMut<WasmStreaming>| {
+                                streaming.enabled = event.value;
+                            }
+                        ),
+                    ),
+                    text("Streaming", 24.),
+                    (
+                        button((text("Pause", 24.), StreamPauseText)),
+                        observe(|_: On<Activate>, mut streaming: ResMut<WasmStreaming>| {
+                            streaming.paused = !streaming.paused;
+                        })
+                    ),
+                ],
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    ..default()
+                },
+                children![
+                    text("Host calls per frame:", 24.),
+                    (
+                        slider(10., 5000., WasmStreaming::default().batch as f32),
+                        SettingSlider::StreamingBatch,
+                        observe(
+                            |event: On<ValueChange<f32>>, mut streaming: ResMut<WasmStreaming>| {
+                                streaming.batch = event.value as usize;
+                            }
+                        ),
+                    ),
+                ],
+            ),
+            (text("", 24.), BreakpointText),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    ..default()
+                },
+                children![
+                    text("Break on call:", 24.),
+                    (
+                        slider(0., 10000., 0.),
+                        SettingSlider::BreakOnCall,
+                        observe(
+                            |event: On<ValueChange<f32>>, mut breakpoints: ResMut<Breakpoints>| {
+                                // 0 is off
+                                breakpoints.calls =
+                                    (event.value >= 1.).then_some(event.value as u32);
+                            }
+                        ),
+                    ),
+                ],
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        button(text("Continue", 24.)),
+                        observe(|_: On<Activate>, mut streaming: ResMut<WasmStreaming>| {
+                            streaming.paused = false;
+                        })
+                    ),
+                    (
+                        button(text("Step", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             pathfinding: Option<Res<WasmPathfinding>>,
+                             mut streaming: ResMut<WasmStreaming>| {
+                                if let Some(pathfinding) = pathfinding {
+                                    pathfinding.step();
+                                    streaming.paused = false;
+                                }
+                            }
+                        )
+                    ),
+                    (
+                        button(text("Abort", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             pathfinding: Option<ResMut<WasmPathfinding>>,
+                             mut streaming: ResMut<WasmStreaming>| {
+                                if let Some(mut pathfinding) = pathfinding {
+                                    pathfinding.abort();
+                                }
+                                streaming.paused = false;
+                            }
+                        )
+                    ),
+                ],
+            ),
+            (
+                button(text("Profile run", 24.)),
+                observe(
+                    |_: On<Activate>,
+                     pathfinding: Option<Res<WasmPathfinding>>,
+                     mut request: ResMut<ProfileRequest>,
+                     mut mut_state: ResMut<NextState<WasmState>>| {
+                        let Some(pathfinding) = pathfinding else {
+                            return;
+                        };
+                        let name = pathfinding.file().file_stem().unwrap().to_string_lossy();
+                        if let Some(file) = FileDialog::new()
+                            .add_filter("Firefox profile", &["json"])
+                            .set_directory(current_dir().unwrap())
+                            .set_file_name(format!("{name}.profile.json"))
+                            .save_file()
+                        {
+                            request.0 = Some(file);
+                            mut_state.set(WasmState::Run);
+                        }
+                    }
+                )
+            ),
+            (text("", 24.), ProfileSummaryText),
+            (text("Reference: ?", 24.), ReferenceText),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        button((text("Solver: ?", 24.), ReferenceSolverText)),
+                        observe(|_: On<Activate>, mut settings: ResMut<ReferenceSettings>| {
+                            settings.solver = settings.solver.next();
+                        })
+                    ),
+                    (
+                        button(text("Run reference", 24.)),
+                        observe(|_: On<Activate>, mut commands: Commands| {
+                            commands.run_system_cached(reference_run);
+                        })
+                    ),
+                ],
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        checkbox(),
+                        SettingCheckbox::ReferenceOverlay,
+                        observe(
+                            |event: On<ValueChange<bool>>,
+                             mut settings: ResMut<ReferenceSettings>| {
+                                settings.overlay = event.value;
+                            }
+                        ),
+                    ),
+                    text("Reference overlay", 24.),
+                ],
+            ),
+            (
+                button(text("Fuzz", 24.)),
+                observe(
+                    |_: On<Activate>,
+                     pathfinding: Option<Res<WasmPathfinding>>,
+                     mut fuzzing: ResMut<Fuzzing>| {
+                        if pathfinding.is_some() {
+                            fuzzing.toggle();
+                        }
+                    }
+                )
+            ),
+            (text("Fuzzing: off", 24.), FuzzText),
+            (text("Areas: ?", 24.), ReachabilityText),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        checkbox(),
+                        SettingCheckbox::ShowAreas,
+                        observe(
+                            |event: On<ValueChange<bool>>,
+                             mut overlay: ResMut<ReachabilityOverlay>| {
+                                overlay.0 = event.value;
+                            }
+                        ),
+                    ),
+                    text("Show areas", 24.),
+                ],
+            ),
+            (
+                button((text("Movement: ?", 24.), MovementText)),
+                observe(
+                    |_: On<Activate>,
+                     mut movement: ResMut<Movement>,
+                     pathfinding: Option<Res<WasmPathfinding>>,
+                     mut mut_state: ResMut<NextState<WasmState>>| {
+                        *movement = movement.next();
+                        if pathfinding.is_some() {
+                            mut_state.set(WasmState::Run);
+                        }
+                    }
+                )
+            ),
+            (
+                button((text("Labels: ?", 24.), LabelModeText)),
+                observe(
+                    |_: On<Activate>, mut mode: ResMut<LabelMode>, labels: Res<TileLabels>| {
+                        *mode = mode.next(labels.max_values());
+                    }
+                )
+            ),
+            (
+                button((text("Colormap: ?", 24.), ColormapText)),
+                observe(|_: On<Activate>, mut colormap: ResMut<Colormap>| {
+                    *colormap = colormap.next();
+                })
+            ),
+            (
+                Node {
+                    display: Display::None,
+                    width: percent(100),
+                    column_gap: px(4),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                HeatmapLegend,
+            ),
+            (
+                button((text("Crowd: off", 24.), CrowdText)),
+                observe(|_: On<Activate>, mut crowd: ResMut<Crowd>| {
+                    crowd.enabled = !crowd.enabled;
+                })
+            ),
+            separator(),
+            text("Layers", 32.),
+            (
+                Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    width: percent(100),
+                    row_gap: px(4),
+                    ..default()
+                },
+                LayersList,
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    ..default()
+                },
+                children![
+                    text("Fox speed:", 24.),
+                    (
+                        slider(1., 16., FoxWalk::default().speed),
+                        observe(|event: On<ValueChange<f32>>, mut walk: ResMut<FoxWalk>| {
+                            walk.speed = event.value;
+                        }),
+                    )
+                ]
+            ),
             separator(),
             (text("Map size: 0x0", 32.), MapSizeText),
             (
@@ -120,7 +615,12 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
                 children![
                     text("X:", 24.),
                     (
-                        slider(2., 128., map_size.0.x as f32),
+                        slider(
+                            MIN_MAP_SIZE as f32,
+                            MAX_MAP_SIZE as f32,
+                            map_size.0.x as f32
+                        ),
+                        MapSizeSlider::X,
                         observe(
                             |event: On<ValueChange<f32>>, mut map_size: ResMut<MapSize>| {
                                 map_size.0.x = event.value as u32;
@@ -138,7 +638,12 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
                 children![
                     text("Y:", 24.),
                     (
-                        slider(2., 128., map_size.0.y as f32),
+                        slider(
+                            MIN_MAP_SIZE as f32,
+                            MAX_MAP_SIZE as f32,
+                            map_size.0.y as f32
+                        ),
+                        MapSizeSlider::Y,
                         observe(
                             |event: On<ValueChange<f32>>, mut map_size: ResMut<MapSize>| {
                                 map_size.0.y = event.value as u32;
@@ -147,12 +652,200 @@ fn ui_startup(mut commands: Commands, map_size: Res<MapSize>) {
                     )
                 ]
             ),
+            separator(),
+            text("Map image", 32.),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        button(text("Import PNG", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             mut commands: Commands,
+                             settings: Res<MapImageSettings>| {
+                                if let Some(file) =
+                                    FileDialog::new().add_filter("PNG", &["png"]).pick_file()
+                                {
+                                    match settings.load(&file) {
+                                        Ok(map) => map::load_map(&mut commands, map),
+                                        Err(err) => error!("{}", err),
+                                    }
+                                }
+                            }
+                        ),
+                    ),
+                    (
+                        button(text("Export PNG", 24.)),
+                        observe(
+                            |_: On<Activate>,
+                             map: Res<Map>,
+                             settings: Res<MapImageSettings>,
+                             statistics: Res<Statistics>,
+                             fox: Single<&MapPos, With<Fox>>,
+                             flag: Single<&MapPos, With<Flag>>| {
+                                let Some(file) = FileDialog::new()
+                                    .add_filter("PNG", &["png"])
+                                    .set_file_name("map.png")
+                                    .save_file()
+                                else {
+                                    return;
+                                };
+
+                                let goals = [(**fox, "fox.png"), (**flag, "flag.png")];
+                                if let Err(err) = map_image::render(
+                                    &map,
+                                    &goals,
+                                    settings.overlay,
+                                    statistics.output.as_ref(),
+                                )
+                                .and_then(|image| image.save(&file))
+                                {
+                                    error!("{}", err);
+                                }
+                            }
+                        ),
+                    ),
+                ],
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    ..default()
+                },
+                children![
+                    text("Pixels per tile:", 24.),
+                    (
+                        slider(1., 32., MapImageSettings::default().pixels_per_tile as f32),
+                        SettingSlider::PixelsPerTile,
+                        observe(
+                            |event: On<ValueChange<f32>>,
+                             mut settings: ResMut<MapImageSettings>| {
+                                settings.pixels_per_tile = event.value as u32;
+                            },
+                        ),
+                    )
+                ]
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    ..default()
+                },
+                children![
+                    text("Threshold:", 24.),
+                    (
+                        slider(0., 1., MapImageSettings::default().threshold),
+                        SettingSlider::Threshold,
+                        observe(
+                            |event: On<ValueChange<f32>>,
+                             mut settings: ResMut<MapImageSettings>| {
+                                settings.threshold = event.value;
+                            },
+                        ),
+                    )
+                ]
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    ..default()
+                },
+                children![
+                    (
+                        checkbox(),
+                        SettingCheckbox::BrightIsWall,
+                        observe(
+                            |event: On<ValueChange<bool>>,
+                             mut settings: ResMut<MapImageSettings>| {
+                                settings.invert = event.value;
+                            }
+                        ),
+                    ),
+                    text("Bright is wall", 24.),
+                    (
+                        checkbox(),
+                        SettingCheckbox::ExportOverlay,
+                        observe(
+                            |event: On<ValueChange<bool>>,
+                             mut settings: ResMut<MapImageSettings>| {
+                                settings.overlay = event.value;
+                            }
+                        ),
+                    ),
+                    text("Export overlay", 24.),
+                ],
+            ),
+            separator(),
+            (text("", 24.), StatisticsText),
+            separator(),
+            (
+                Node {
+                    display: Display::Flex,
+                    width: percent(100),
+                    column_gap: px(8),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                children![
+                    text("Run history", 32.),
+                    (
+                        button(text("Clear", 24.)),
+                        observe(|_: On<Activate>, mut history: ResMut<RunHistory>| {
+                            history.runs.clear();
+                            history.shown = None;
+                        })
+                    ),
+                ],
+            ),
+            (
+                Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    width: percent(100),
+                    row_gap: px(4),
+                    ..default()
+                },
+                RunHistoryList,
+            ),
         ],
     ));
 }
 
+// Scrolled with the mouse wheel, it doesn't fit into small windows
+#[derive(Component)]
+pub struct SidePanel;
+
+// Pixels per wheel line
+const SCROLL_LINE_HEIGHT: f32 = 24.;
+
+fn side_panel_scroll(
+    mut mouse_ev: MessageReader<MouseWheel>,
+    panel: Single<(&mut ScrollPosition, &RelativeCursorPosition), With<SidePanel>>,
+) {
+    let (mut scroll, cursor) = panel.into_inner();
+    for ev in mouse_ev.read() {
+        if !cursor.cursor_over() {
+            continue;
+        }
+        // Layout clamps it to the content height
+        scroll.y -= match ev.unit {
+            MouseScrollUnit::Line => ev.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => ev.y,
+        };
+    }
+}
+
 #[derive(Component)]
-struct SelectAlgorithmText;
+pub struct SelectAlgorithmText;
 
 #[derive(Component)]
 struct FPSText;
@@ -168,19 +861,169 @@ fn fps_text_update(
     }
 }
 
+// Widgets of settings that can also be changed elsewhere (workspace open, reference run)
+#[derive(Component)]
+enum SettingCheckbox {
+    HotReloading,
+    Streaming,
+    ReferenceOverlay,
+    ShowAreas,
+    BrightIsWall,
+    ExportOverlay,
+}
+
+#[derive(Component)]
+enum SettingSlider {
+    StreamingBatch,
+    BreakOnCall,
+    PixelsPerTile,
+    Threshold,
+}
+
+fn setting_widgets_update(
+    mut commands: Commands,
+    (hot_reloading, streaming, breakpoints, reference, areas, map_image): (
+        Res<WasmHotReloading>,
+        Res<WasmStreaming>,
+        Res<Breakpoints>,
+        Res<ReferenceSettings>,
+        Res<ReachabilityOverlay>,
+        Res<MapImageSettings>,
+    ),
+    checkboxes: Query<(Entity, &SettingCheckbox, Has<Checked>)>,
+    sliders: Query<(Entity, &SettingSlider, &SliderValue)>,
+) {
+    for (entity, checkbox, checked) in checkboxes.iter() {
+        let value = match checkbox {
+            SettingCheckbox::HotReloading => hot_reloading.0,
+            SettingCheckbox::Streaming => streaming.enabled,
+            SettingCheckbox::ReferenceOverlay => reference.overlay,
+            SettingCheckbox::ShowAreas => areas.0,
+            SettingCheckbox::BrightIsWall => map_image.invert,
+            SettingCheckbox::ExportOverlay => map_image.overlay,
+        };
+        match (value, checked) {
+            (true, false) => {
+                commands.entity(entity).insert(Checked);
+            }
+            (false, true) => {
+                commands.entity(entity).remove::<Checked>();
+            }
+            _ => {}
+        }
+    }
+
+    for (entity, slider, current) in sliders.iter() {
+        let value = match slider {
+            SettingSlider::StreamingBatch => streaming.batch as f32,
+            SettingSlider::BreakOnCall => breakpoints.calls.unwrap_or(0) as f32,
+            SettingSlider::PixelsPerTile => map_image.pixels_per_tile as f32,
+            SettingSlider::Threshold => map_image.threshold,
+        };
+        if current.0 != value {
+            commands.entity(entity).insert(SliderValue(value));
+        }
+    }
+}
+
 #[derive(Component)]
 struct MapSizeText;
 
+#[derive(Component)]
+enum MapSizeSlider {
+    X,
+    Y,
+}
+
+// Map size can also be changed by PNG import
+fn map_size_sliders_update(
+    mut commands: Commands,
+    size: Res<MapSize>,
+    sliders: Query<(Entity, &MapSizeSlider)>,
+) {
+    for (entity, slider) in sliders.iter() {
+        let value = match slider {
+            MapSizeSlider::X => size.0.x,
+            MapSizeSlider::Y => size.0.y,
+        };
+        commands.entity(entity).insert(SliderValue(value as f32));
+    }
+}
+
 fn map_size_text_update(size: Res<MapSize>, mut size_text: Single<&mut Text, With<MapSizeText>>) {
     size_text.0 = format!("Map Size: {}x{}", size.0.x, size.0.y);
 }
 
+#[derive(Component)]
+struct MovementText;
+
+fn movement_text_update(
+    movement: Res<Movement>,
+    mut movement_text: Single<&mut Text, With<MovementText>>,
+) {
+    movement_text.0 = format!("Movement: {}", movement.name());
+}
+
+#[derive(Component)]
+struct LabelModeText;
+
+fn label_mode_text_update(
+    mode: Res<LabelMode>,
+    mut mode_text: Single<&mut Text, With<LabelModeText>>,
+) {
+    mode_text.0 = format!("Labels: {}", mode.name());
+}
+
+#[derive(Component)]
+struct ColormapText;
+
+fn colormap_text_update(
+    colormap: Res<Colormap>,
+    mut colormap_text: Single<&mut Text, With<ColormapText>>,
+) {
+    colormap_text.0 = format!("Colormap: {}", colormap.name());
+}
+
+#[derive(Component)]
+struct StreamPauseText;
+
+fn stream_pause_text_update(
+    streaming: Res<WasmStreaming>,
+    mut pause_text: Single<&mut Text, With<StreamPauseText>>,
+) {
+    pause_text.0 = match streaming.paused {
+        true => "Resume".to_owned(),
+        false => "Pause".to_owned(),
+    };
+}
+
+#[derive(Component)]
+struct ReferenceSolverText;
+
+fn reference_solver_text_update(
+    settings: Res<ReferenceSettings>,
+    mut solver_text: Single<&mut Text, With<ReferenceSolverText>>,
+) {
+    solver_text.0 = format!("Solver: {}", settings.solver.name());
+}
+
+#[derive(Component)]
+struct StatisticsText;
+
+fn statistics_text_update(
+    statistics: Res<Statistics>,
+    mut statistics_text: Single<&mut Text, With<StatisticsText>>,
+) {
+    statistics_text.0 = statistics.to_string();
+}
+
 pub struct SettingsPlugin;
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(UiWidgetsPlugins)
             // UI
             .add_systems(Startup, ui_startup)
+            .add_systems(Update, side_panel_scroll)
             .add_plugins(FrameTimeDiagnosticsPlugin::default())
             .add_systems(
                 Update,
@@ -188,7 +1031,53 @@ impl Plugin for SettingsPlugin {
             )
             .add_systems(
                 Update,
-                map_size_text_update.run_if(|size: Res<MapSize>| size.is_changed()),
+                setting_widgets_update.run_if(
+                    |hot_reloading: Res<WasmHotReloading>,
+                     streaming: Res<WasmStreaming>,
+                     breakpoints: Res<Breakpoints>,
+                     reference: Res<ReferenceSettings>,
+                     areas: Res<ReachabilityOverlay>,
+                     map_image: Res<MapImageSettings>| {
+                        hot_reloading.is_changed()
+                            || streaming.is_changed()
+                            || breakpoints.is_changed()
+                            || reference.is_changed()
+                            || areas.is_changed()
+                            || map_image.is_changed()
+                    },
+                ),
+            )
+            .add_systems(
+                Update,
+                (map_size_text_update, map_size_sliders_update)
+                    .run_if(|size: Res<MapSize>| size.is_changed()),
+            )
+            .add_systems(
+                Update,
+                movement_text_update.run_if(|movement: Res<Movement>| movement.is_changed()),
+            )
+            .add_systems(
+                Update,
+                label_mode_text_update.run_if(|mode: Res<LabelMode>| mode.is_changed()),
+            )
+            .add_systems(
+                Update,
+                colormap_text_update.run_if(|colormap: Res<Colormap>| colormap.is_changed()),
+            )
+            .add_systems(
+                Update,
+                stream_pause_text_update
+                    .run_if(|streaming: Res<WasmStreaming>| streaming.is_changed()),
+            )
+            .add_systems(
+                Update,
+                reference_solver_text_update
+                    .run_if(|settings: Res<ReferenceSettings>| settings.is_changed()),
+            )
+            .add_systems(
+                Update,
+                statistics_text_update
+                    .run_if(|statistics: Res<Statistics>| statistics.is_changed()),
             );
     }
 }
@@ -0,0 +1,224 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    SPRITE_SIZE,
+    api::PathOutput,
+    goals::{FOX_FRAMES, Flag, Fox},
+    map::MapPos,
+    statistics::Statistics,
+    wasm::tile_center,
+};
+
+const FRAME_TIME: f32 = 0.1;
+const CELEBRATION_TIME: f32 = 1.5;
+const FAILURE_TIME: f32 = 1.;
+const HOP_HEIGHT: f32 = SPRITE_SIZE as f32 / 3.;
+const SHAKE_WIDTH: f32 = SPRITE_SIZE as f32 / 6.;
+const FAILURE_COLOR: Color = Color::srgb(1., 0.3, 0.3);
+
+const CONFETTI: usize = 80;
+const CONFETTI_SIZE: f32 = 2.;
+const CONFETTI_SPEED: f32 = SPRITE_SIZE as f32 * 8.;
+const CONFETTI_GRAVITY: f32 = SPRITE_SIZE as f32 * 12.;
+const CONFETTI_COLORS: [Color; 5] = [
+    Color::srgb(0.95, 0.25, 0.3),
+    Color::srgb(0.25, 0.6, 0.95),
+    Color::srgb(0.3, 0.85, 0.35),
+    Color::srgb(1., 0.85, 0.2),
+    Color::srgb(0.75, 0.35, 0.9),
+];
+// Above goals and crowd
+const CONFETTI_Z: f32 = 3.;
+
+#[derive(Debug, Clone, Default)]
+enum WalkState {
+    #[default]
+    Idle,
+    // Progress is measured in path steps
+    Walking {
+        path: Vec<MapPos>,
+        progress: f32,
+    },
+    Celebrating {
+        at: Vec3,
+        timer: Timer,
+    },
+    Failing(Timer),
+}
+
+// Fox walks a copy of its start position, `MapPos` stays where the user put it
+#[derive(Resource, Debug, Clone)]
+pub struct FoxWalk {
+    // Tiles per second
+    pub speed: f32,
+    state: WalkState,
+}
+impl Default for FoxWalk {
+    fn default() -> Self {
+        FoxWalk {
+            speed: 4.,
+            state: WalkState::Idle,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Confetti {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+fn walk_start(statistics: Res<Statistics>, mut walk: ResMut<FoxWalk>) {
+    walk.state = match &statistics.output {
+        Some(PathOutput {
+            path, cost: Ok(_), ..
+        }) if path.len() > 1 => WalkState::Walking {
+            path: path.clone(),
+            progress: 0.,
+        },
+        Some(PathOutput { cost: Err(_), .. }) => {
+            WalkState::Failing(Timer::from_seconds(FAILURE_TIME, TimerMode::Once))
+        }
+        _ => WalkState::Idle,
+    };
+}
+
+fn spawn_confetti(commands: &mut Commands, center: Vec2) {
+    for _ in 0..CONFETTI {
+        // Upwards fan
+        let angle = PI / 2. + (fastrand::f32() - 0.5) * PI * 0.8;
+        let speed = CONFETTI_SPEED * (0.5 + fastrand::f32());
+
+        commands.spawn((
+            Sprite::from_color(
+                CONFETTI_COLORS[fastrand::usize(..CONFETTI_COLORS.len())],
+                Vec2::splat(CONFETTI_SIZE),
+            ),
+            Transform::from_translation(center.extend(CONFETTI_Z)),
+            Confetti {
+                velocity: Vec2::from_angle(angle) * speed,
+                lifetime: Timer::from_seconds(CELEBRATION_TIME, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn walk_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut walk: ResMut<FoxWalk>,
+    fox: Single<(Ref<MapPos>, &mut Transform, &mut Sprite), With<Fox>>,
+    flag: Single<&MapPos, With<Flag>>,
+) {
+    let (start, mut transform, mut sprite) = fox.into_inner();
+    let home: Transform = (*start).into();
+    let speed = walk.speed;
+
+    // Dragging the fox cancels the walk
+    if start.is_changed() {
+        walk.state = WalkState::Idle;
+    }
+
+    match &mut walk.state {
+        // Written only on change, so idle fox doesn't trigger change detection
+        WalkState::Idle => {
+            transform.set_if_neq(home);
+
+            let frame = sprite.texture_atlas.as_ref().map(|atlas| atlas.index);
+            if sprite.color != Color::WHITE || frame.is_some_and(|frame| frame != 0) {
+                sprite.color = Color::WHITE;
+                if let Some(atlas) = &mut sprite.texture_atlas {
+                    atlas.index = 0;
+                }
+            }
+        }
+        WalkState::Walking { path, progress } => {
+            *progress += speed * time.delta_secs();
+
+            let step = *progress as usize;
+            if step + 1 >= path.len() {
+                let end: Transform = path[path.len() - 1].into();
+                transform.translation = end.translation;
+
+                if path[path.len() - 1] == **flag {
+                    spawn_confetti(&mut commands, tile_center(**flag));
+                }
+                walk.state = WalkState::Celebrating {
+                    at: end.translation,
+                    timer: Timer::from_seconds(CELEBRATION_TIME, TimerMode::Once),
+                };
+                return;
+            }
+
+            let from: Transform = path[step].into();
+            let to: Transform = path[step + 1].into();
+            transform.translation = from.translation.lerp(to.translation, progress.fract());
+
+            if to.translation.x != from.translation.x {
+                sprite.flip_x = to.translation.x < from.translation.x;
+            }
+            if let Some(atlas) = &mut sprite.texture_atlas {
+                atlas.index = (time.elapsed_secs() / FRAME_TIME) as usize % FOX_FRAMES as usize;
+            }
+        }
+        WalkState::Celebrating { at, timer } => {
+            timer.tick(time.delta());
+
+            // Little hops in place
+            let hop = (timer.fraction() * PI * 3.).sin().abs() * HOP_HEIGHT;
+            transform.translation = *at + Vec3::Y * hop;
+
+            if timer.is_finished() {
+                sprite.flip_x = false;
+                walk.state = WalkState::Idle;
+            }
+        }
+        WalkState::Failing(timer) => {
+            timer.tick(time.delta());
+
+            let shake = (timer.elapsed_secs() * 40.).sin() * SHAKE_WIDTH;
+            transform.translation = home.translation + Vec3::X * shake;
+            sprite.color = FAILURE_COLOR;
+
+            if timer.is_finished() {
+                walk.state = WalkState::Idle;
+            }
+        }
+    }
+}
+
+fn confetti_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut confetti: Query<(Entity, &mut Confetti, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut piece, mut transform, mut sprite) in confetti.iter_mut() {
+        piece.lifetime.tick(time.delta());
+        if piece.lifetime.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        piece.velocity.y -= CONFETTI_GRAVITY * time.delta_secs();
+        transform.translation += (piece.velocity * time.delta_secs()).extend(0.);
+        transform.rotate_z(time.delta_secs() * 10.);
+        sprite.color.set_alpha(1. - piece.lifetime.fraction());
+    }
+}
+
+pub struct FoxWalkPlugin;
+impl Plugin for FoxWalkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FoxWalk>().add_systems(
+            Update,
+            (
+                walk_start.run_if(|statistics: Res<Statistics>| statistics.is_changed()),
+                walk_update,
+                confetti_update,
+            )
+                .chain(),
+        );
+    }
+}
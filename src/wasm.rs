@@ -1,215 +1,908 @@
 use std::{
-    fs,
-    path::PathBuf,
-    sync::Mutex,
-    time::{Duration, SystemTime},
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        Arc, LazyLock, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use bevy::{prelude::*, sprite_render::TilemapChunkTileData, time::common_conditions::on_timer};
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future},
+};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use wasmtime::{
-    Config, Engine, Store,
-    component::{Component, HasSelf, Linker},
+    CallHook, Config, Engine, Store, UpdateDeadline,
+    component::{Component, Linker},
 };
 
 use crate::{
     SPRITE_SIZE,
-    api::{Pathfinding, TimelineAction, WasmRunner, host},
+    api::{
+        ApiVersion, Debugger, PathOutput, Pathfinding, PathfindingPre, RunInput, Timeline,
+        TimelineAction, TimelineEntry, WasmRunner,
+    },
+    cache,
+    crowd::FlowField,
+    debugger::{BreakpointHit, Breakpoints},
     goals::{Flag, Fox},
-    map::{Map, MapPos},
+    heatmap::{Colormap, Heatmap, ScalarRange},
+    history::{RunHistory, RunRecord},
+    labels::TileLabels,
+    layers::{Layer, Layers},
+    map::{Map, MapPos, MapSize},
+    movement::Movement,
+    profiling::{self, Profile, ProfileRequest, ProfileSummary},
+    statistics::Statistics,
+    ui::SelectAlgorithmText,
 };
 
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash, States)]
 pub enum WasmState {
     Run,
+    // Guest is suspended between frames, see `WasmStreaming`
+    Streaming,
     Error(String),
     #[default]
     Idle,
 }
 
+// Shared by every load and reload, so compiled components can be cached
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    // Epoch interruption drives the sampling profiler and streaming yields
+    Engine::new(
+        Config::new()
+            .wasm_component_model(true)
+            .async_support(true)
+            .epoch_interruption(true),
+    )
+    .expect("Engine config is valid")
+});
+
+// Fresh instance for every run, a running guest owns it until it returns
+struct Instance {
+    module: Pathfinding,
+    store: Store<WasmRunner>,
+}
+impl Instance {
+    async fn run(&mut self) -> wasmtime::Result<()> {
+        let input = self.store.data().input.clone();
+        self.module.call_run(&mut self.store, &input).await
+    }
+}
+
+// Epoch ticks while `check` runs, its deadline is checked on every tick
+const CHECK_TICK: Duration = Duration::from_millis(10);
+
+// `check` error when the guest doesn't return before its deadline
+#[derive(Debug)]
+pub struct CheckTimeout(pub Duration);
+impl fmt::Display for CheckTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Guest didn't finish in {} ms", self.0.as_millis())
+    }
+}
+impl std::error::Error for CheckTimeout {}
+
+type RunFuture = Pin<Box<dyn Future<Output = (WasmRunner, wasmtime::Result<()>)> + Send>>;
+
 #[derive(Resource)]
 pub struct WasmPathfinding {
     file: PathBuf,
-    module: Pathfinding,
-    store: Mutex<Store<WasmRunner>>,
+    version: ApiVersion,
+    component: Component,
+    // Instantiated for every run
+    pre: PathfindingPre,
+    // Timeline of the current run, replaced when a new one starts
+    timeline: Arc<Mutex<Timeline>>,
+    // Map size the timeline was drawn on, its positions mean nothing on other sizes
+    timeline_size: UVec2,
+    debugger: Arc<Mutex<Debugger>>,
+    running: Mutex<Option<RunFuture>>,
+    // Runner of the last finished run, its instance is dropped
+    finished: Mutex<Option<WasmRunner>>,
 }
 impl WasmPathfinding {
-    pub fn load(file: &PathBuf) -> Result<WasmPathfinding, wasmtime::Error> {
-        let engine = Engine::new(Config::new().wasm_component_model(true))?;
+    pub fn load(file: &Path) -> Result<WasmPathfinding, wasmtime::Error> {
+        let engine = &*ENGINE;
 
         info!("Loading {}", file.display());
 
-        let component = Component::from_file(&engine, file)?;
-        let mut linker = Linker::new(&engine);
+        let component = cache::load_component(engine, file)?;
+        let version = ApiVersion::detect(engine, &component)?;
+        let mut linker = Linker::new(engine);
+
+        info!("Detected API {}", version);
 
-        wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
+        wasmtime_wasi::p2::add_to_linker_async(&mut linker)?;
+        version.add_to_linker(&mut linker)?;
 
-        let data = WasmRunner::default();
-        let mut store = Store::new(component.engine(), data);
-        host::add_to_linker::<_, HasSelf<_>>(&mut linker, |data: &mut WasmRunner| data)?;
         Ok(WasmPathfinding {
-            file: file.clone(),
-            module: Pathfinding::instantiate(&mut store, &component, &linker)?,
-            store: Mutex::new(store),
+            file: file.to_path_buf(),
+            version,
+            pre: PathfindingPre::new(version, &component, &linker)?,
+            component,
+            timeline: Arc::default(),
+            timeline_size: UVec2::ZERO,
+            debugger: Arc::default(),
+            running: Mutex::new(None),
+            finished: Mutex::new(None),
         })
     }
 
-    pub fn run(
+    // New store and timeline, nothing is shared with previous runs
+    fn instantiate(&mut self, input: RunInput, batch: Option<usize>) -> wasmtime::Result<Instance> {
+        self.timeline = Arc::default();
+        self.timeline_size = uvec2(input.map.width(), input.map.height());
+        *self.finished.get_mut().unwrap() = None;
+
+        self.new_instance(input, batch, self.timeline.clone(), self.debugger.clone())
+    }
+
+    // Instance drawing into `timeline`, paused by `debugger`
+    fn new_instance(
+        &self,
+        input: RunInput,
+        batch: Option<usize>,
+        timeline: Arc<Mutex<Timeline>>,
+        debugger: Arc<Mutex<Debugger>>,
+    ) -> wasmtime::Result<Instance> {
+        let engine = self.component.engine();
+        let mut runner = WasmRunner::new(engine, timeline, debugger);
+        runner.start(input, batch);
+
+        let mut store = Store::new(engine, runner);
+        profiling::configure(&mut store);
+        store.call_hook(|mut store, hook| {
+            if matches!(hook, CallHook::CallingHost) {
+                store.data_mut().host_call();
+            }
+            Ok(())
+        });
+
+        let module = block_on(self.pre.instantiate(&mut store))?;
+        Ok(Instance { module, store })
+    }
+
+    // Runs the guest to the end on its own instance, the current run,
+    // its drawing and breakpoints are untouched
+    // Fails with `CheckTimeout` if the guest is still running after `timeout`
+    pub fn check(
+        &self,
+        input: RunInput,
+        timeout: Duration,
+    ) -> wasmtime::Result<Option<PathOutput>> {
+        let mut instance = self.new_instance(input, None, Arc::default(), Arc::default())?;
+        let deadline = Instant::now() + timeout;
+        instance
+            .store
+            .epoch_deadline_callback(move |_| match Instant::now() < deadline {
+                true => Ok(UpdateDeadline::Continue(1)),
+                false => Err(CheckTimeout(timeout).into()),
+            });
+
+        // Guest stuck in a loop without host calls is only stopped by the epoch
+        let engine = self.component.engine();
+        let finished = AtomicBool::new(false);
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                while !finished.load(Ordering::Relaxed) {
+                    thread::sleep(CHECK_TICK);
+                    engine.increment_epoch();
+                }
+            });
+            let result = block_on(instance.run());
+            finished.store(true, Ordering::Relaxed);
+            result
+        })?;
+        Ok(instance.store.into_data().output)
+    }
+
+    pub fn file(&self) -> &PathBuf {
+        &self.file
+    }
+
+    // File name and API version, shown in UI
+    pub fn name(&self) -> String {
+        format!(
+            "{} ({})",
+            self.file.file_name().unwrap().to_string_lossy(),
+            self.version
+        )
+    }
+
+    // Drawing of the last run and its layer names
+    pub fn timeline(&self) -> (Vec<TimelineEntry>, Vec<String>) {
+        let timeline = self.timeline.lock().unwrap();
+        (timeline.entries.clone(), timeline.layers.clone())
+    }
+
+    pub fn layer_names(&self) -> Vec<String> {
+        self.timeline.lock().unwrap().layers.clone()
+    }
+
+    // Shows a saved run without running the guest again
+    pub fn restore_timeline(
         &mut self,
-        tiles: Vec<Vec<bool>>,
-        fox_pos: (u32, u32),
-        flag_pos: (u32, u32),
-    ) -> wasmtime::Result<()> {
-        return self.module.guest().call_run(
-            &mut (*self.store.lock().unwrap()),
-            &tiles,
-            fox_pos,
-            flag_pos,
-        );
+        entries: Vec<TimelineEntry>,
+        layers: Vec<String>,
+        map_size: UVec2,
+    ) {
+        self.abort();
+        self.timeline = Arc::new(Mutex::new(Timeline { entries, layers }));
+        self.timeline_size = map_size;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.lock().unwrap().is_some()
+    }
+
+    // Guest yields every `batch` host calls and on breakpoints, `poll` resumes it
+    // A guest still running is dropped with its store
+    pub fn start(&mut self, input: RunInput, batch: Option<usize>) -> wasmtime::Result<()> {
+        self.abort();
+        let mut instance = self.instantiate(input, batch)?;
+
+        *self.running.get_mut().unwrap() = Some(Box::pin(async move {
+            let result = instance.run().await;
+            (instance.store.into_data(), result)
+        }));
+        Ok(())
+    }
+
+    // Runs the guest until it yields, `Some` once it has returned
+    pub fn poll(&mut self) -> Option<wasmtime::Result<()>> {
+        let running = self.running.get_mut().unwrap();
+        let (runner, result) = block_on(future::poll_once(running.as_mut()?))?;
+
+        *running = None;
+        *self.finished.get_mut().unwrap() = Some(runner);
+        Some(result)
+    }
+
+    // Drops the running guest with its store
+    pub fn abort(&mut self) {
+        *self.running.get_mut().unwrap() = None;
+    }
+
+    pub fn set_breakpoints(&self, breakpoints: Breakpoints) {
+        self.debugger.lock().unwrap().breakpoints = breakpoints;
+    }
+
+    // Guest breaks again after its next host call
+    pub fn step(&self) {
+        self.debugger.lock().unwrap().step = true;
+    }
+
+    fn take_breakpoint_hit(&self) -> Option<String> {
+        self.debugger.lock().unwrap().hit.take()
+    }
+
+    // Runs the guest to the end, also writes its profile to `file`
+    pub fn profile(&mut self, input: RunInput, file: &Path) -> wasmtime::Result<ProfileSummary> {
+        self.abort();
+        let mut instance = self.instantiate(input, None)?;
+
+        let name = file.file_stem().unwrap_or_default().to_string_lossy();
+        let profile = Profile::start(&mut instance.store, &self.component, &name);
+        let result = block_on(instance.run());
+        let summary = profile.finish(&mut instance.store, file);
+
+        *self.finished.get_mut().unwrap() = Some(instance.store.into_data());
+        result?;
+        summary
+    }
+
+    // Input and statistics of the last finished run
+    fn finished_run(&self, statistics: &mut Statistics) -> Option<RunInput> {
+        let finished = self.finished.lock().unwrap();
+        let runner = finished.as_ref()?;
+
+        statistics.output = runner.output.clone();
+        statistics.queried_nodes = runner.queries.len();
+        statistics.queries = runner.queries.values().sum();
+        statistics.expanded = runner.search.expanded;
+        statistics.max_open = runner.search.max_open;
+        Some(runner.input.clone())
+    }
+}
+
+// Runs guest in small steps between frames, so huge maps show progress
+#[derive(Resource, Reflect, Debug, Clone)]
+pub struct WasmStreaming {
+    pub enabled: bool,
+    // Host calls per frame, guest waits for its drawing to be shown
+    pub batch: usize,
+    pub paused: bool,
+}
+impl Default for WasmStreaming {
+    fn default() -> Self {
+        WasmStreaming {
+            enabled: false,
+            batch: 200,
+            paused: false,
+        }
     }
 }
 
 fn wasm_clean(
     mut commands: Commands,
-    tiles_map: Single<(Entity, &TilemapChunkTileData)>,
+    mut map: ResMut<Map>,
     gizmos: Query<Entity, With<Gizmo>>,
+    shapes: Query<Entity, With<WasmShape>>,
+    mut labels: ResMut<TileLabels>,
 ) {
-    let (entity, map) = *tiles_map;
-    commands.entity(entity).insert(TilemapChunkTileData(
-        map.iter()
-            .map(|tile| {
-                let mut tile = tile.unwrap_or_default();
-                tile.color = Color::WHITE;
-                Some(tile)
-            })
-            .collect(),
-    ));
+    map.0
+        .iter_mut()
+        .flatten()
+        .for_each(|tile| tile.color = Color::WHITE);
 
     gizmos.iter().for_each(|g| commands.entity(g).despawn());
+    shapes.iter().for_each(|s| commands.entity(s).despawn());
+    labels.0.clear();
 }
 
 fn wasm_run(
     mut wasm: ResMut<WasmPathfinding>,
     map: Res<Map>,
+    movement: Res<Movement>,
     fox_pos: Single<&MapPos, With<Fox>>,
     flag_pos: Single<&MapPos, With<Flag>>,
+    mut statistics: ResMut<Statistics>,
+    mut layers: ResMut<Layers>,
+    mut profile_request: ResMut<ProfileRequest>,
+    mut profile_summary: ResMut<ProfileSummary>,
+    mut streaming: ResMut<WasmStreaming>,
+    mut hit: ResMut<BreakpointHit>,
+    mut history: ResMut<RunHistory>,
     mut mut_state: ResMut<NextState<WasmState>>,
 ) {
     println!("Fox position: {:?}", *fox_pos);
     println!("Flag position: {:?}", *flag_pos);
 
-    if let Err(err) = wasm.run(
-        map.to_pathfinding_map(),
-        (**fox_pos).into(),
-        (**flag_pos).into(),
-    ) {
+    *statistics = Statistics::new(*movement, **fox_pos, **flag_pos);
+
+    let input = RunInput {
+        map: map.clone(),
+        movement: *movement,
+        start: **fox_pos,
+        end: **flag_pos,
+    };
+    streaming.paused = false;
+    hit.0 = None;
+
+    // Without streaming the guest runs to the end here, unless it hits a breakpoint
+    let batch = streaming.enabled.then_some(streaming.batch);
+    let result = match profile_request.0.take() {
+        Some(file) => wasm
+            .profile(input, &file)
+            .map(|summary| *profile_summary = summary),
+        None => match wasm.start(input, batch) {
+            Ok(()) => match poll_run(&mut wasm, &mut streaming, &mut hit) {
+                Some(result) => result,
+                None => {
+                    layers.sync(&wasm.layer_names());
+                    mut_state.set(WasmState::Streaming);
+                    return;
+                }
+            },
+            Err(err) => Err(err),
+        },
+    };
+
+    finish_run(
+        &wasm,
+        result,
+        &mut statistics,
+        &mut layers,
+        &mut history,
+        &mut mut_state,
+    );
+}
+
+fn wasm_stream(
+    mut wasm: ResMut<WasmPathfinding>,
+    mut streaming: ResMut<WasmStreaming>,
+    mut hit: ResMut<BreakpointHit>,
+    mut statistics: ResMut<Statistics>,
+    mut layers: ResMut<Layers>,
+    mut history: ResMut<RunHistory>,
+    mut mut_state: ResMut<NextState<WasmState>>,
+) {
+    // Aborted, or replaced by hot reload or workspace open
+    if !wasm.is_running() {
+        hit.0 = None;
+        mut_state.set(WasmState::Idle);
+        return;
+    }
+    if streaming.paused {
+        return;
+    }
+    if hit.0.is_some() {
+        hit.0 = None;
+    }
+
+    match poll_run(&mut wasm, &mut streaming, &mut hit) {
+        Some(result) => finish_run(
+            &wasm,
+            result,
+            &mut statistics,
+            &mut layers,
+            &mut history,
+            &mut mut_state,
+        ),
+        // Draws what the guest has produced so far
+        None => layers.sync(&wasm.layer_names()),
+    }
+}
+
+// Pauses streaming when the guest stopped on a breakpoint
+fn poll_run(
+    wasm: &mut WasmPathfinding,
+    streaming: &mut WasmStreaming,
+    hit: &mut BreakpointHit,
+) -> Option<wasmtime::Result<()>> {
+    let result = wasm.poll();
+
+    if let Some(reason) = wasm.take_breakpoint_hit()
+        && result.is_none()
+    {
+        info!("Guest paused: {}", reason);
+        hit.0 = Some(reason);
+        streaming.paused = true;
+    }
+    result
+}
+
+fn finish_run(
+    wasm: &WasmPathfinding,
+    result: wasmtime::Result<()>,
+    statistics: &mut Statistics,
+    layers: &mut Layers,
+    history: &mut RunHistory,
+    mut_state: &mut NextState<WasmState>,
+) {
+    let error = result.err().map(|err| {
         error!("{}", err);
         mut_state.set(WasmState::Error(err.to_string()));
+        err.to_string()
+    });
+
+    if let Some(input) = wasm.finished_run(statistics) {
+        let (entries, layers) = wasm.timeline();
+        history.push(RunRecord {
+            number: 0,
+            algorithm: wasm.name(),
+            input,
+            timeline: Timeline { entries, layers },
+            statistics: statistics.clone(),
+            error,
+        });
     }
 
+    // Triggers drawing even if there are no new layers
+    layers.sync(&wasm.layer_names());
+
     mut_state.set(WasmState::Idle);
 }
 
 const HALF_SIZE: Vec2 = vec2(SPRITE_SIZE as f32 / 2.0, SPRITE_SIZE as f32 / 2.0);
 
+// Theme for search events
+const OPEN_COLOR: Color = Color::srgb(0.55, 0.8, 1.0);
+const CLOSED_COLOR: Color = Color::srgb(1.0, 0.7, 0.45);
+const FOUND_COLOR: Color = Color::srgb(1.0, 0.9, 0.2);
+const PARENT_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+const FLOW_COLOR: Color = Color::srgb(0.2, 0.2, 0.6);
+// Glyph length in tiles
+const FLOW_LENGTH: f32 = 0.6;
+
+// Filled shapes go between tilemap (z = 0) and goals (z = 1)
+const SHAPES_Z: f32 = 0.1;
+const SHAPES_Z_RANGE: f32 = 0.8;
+
+#[derive(Component)]
+struct WasmShape;
+
+pub fn tile_center(pos: MapPos) -> Vec2 {
+    let transform: Transform = pos.into();
+    transform.translation.xy() + HALF_SIZE
+}
+
+fn spawn_gizmo(
+    commands: &mut Commands,
+    gizmo_assets: &mut Assets<GizmoAsset>,
+    gizmo: GizmoAsset,
+    width: f32,
+) {
+    commands.spawn(Gizmo {
+        handle: gizmo_assets.add(gizmo),
+        line_config: GizmoLineConfig { width, ..default() },
+        ..default()
+    });
+}
+
+// Scalar field is row-major from (0, 0), NaN values are skipped
+fn field_values(values: &[f32], width: u32) -> impl Iterator<Item = (MapPos, f32)> + '_ {
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| !value.is_nan())
+        .map(move |(index, value)| {
+            let pos = MapPos {
+                x: index as u32 % width,
+                y: index as u32 / width,
+            };
+            (pos, *value)
+        })
+}
+
 fn show_wasm_actions(
     mut commands: Commands,
     pathfinding: Res<WasmPathfinding>,
+    layers: Res<Layers>,
     mut map: ResMut<Map>,
     mut gizmo_assets: ResMut<Assets<GizmoAsset>>,
+    mut labels: ResMut<TileLabels>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut heatmap: ResMut<Heatmap>,
+    colormap: Res<Colormap>,
+    mut flow_field: ResMut<FlowField>,
 ) {
-    pathfinding
-        .store
-        .lock()
-        .unwrap()
-        .data()
-        .timeline
+    let timeline = pathfinding.timeline.lock().unwrap();
+
+    // Visible actions, bottom layer first, timeline order inside a layer
+    let actions: Vec<(&Layer, &TimelineAction)> = layers
+        .0
         .iter()
-        .for_each(|entry| match *entry {
-            TimelineAction::Line {
-                start,
-                end,
-                color: (r, g, b),
-            } => {
-                let mut gizmo = GizmoAsset::default();
+        .filter(|layer| layer.visible)
+        .flat_map(|layer| {
+            let index = timeline.layers.iter().position(|name| *name == layer.name);
+            timeline
+                .entries
+                .iter()
+                .filter(move |entry| Some(entry.layer) == index)
+                .map(move |entry| (layer, &entry.action))
+        })
+        .collect();
 
-                let pos_start: MapPos = start.into();
-                let pos_end: MapPos = end.into();
+    // Scalars are normalized over all visible layers
+    let (width, height) = (map.width(), map.height());
+    // Fields of another map size (old timelines, saved workspaces) are not drawn
+    let field_len = (width * height) as usize;
+    heatmap.0 = ScalarRange::from_values(actions.iter().flat_map(|(_, action)| match action {
+        TimelineAction::Scalar { value, .. } => vec![*value],
+        TimelineAction::ScalarField { values } if values.len() == field_len => values.clone(),
+        _ => vec![],
+    }));
 
-                let t_start: Transform = pos_start.into();
-                let t_end: Transform = pos_end.into();
+    let shape_z = |index: usize| SHAPES_Z + SHAPES_Z_RANGE * index as f32 / actions.len() as f32;
 
-                gizmo.line_2d(
-                    t_start.translation.xy() + HALF_SIZE,
-                    t_end.translation.xy() + HALF_SIZE,
-                    Color::srgb_u8(r, g, b),
-                );
+    // Only the last parent of each node is drawn
+    let mut parents = HashMap::new();
+    // Only the last flow field is drawn and simulated
+    let mut flow = None;
+    // Guest positions are not checked when they are recorded
+    let mut skipped = 0;
 
-                commands.spawn(Gizmo {
-                    handle: gizmo_assets.add(gizmo),
-                    line_config: GizmoLineConfig {
-                        width: 4.0,
-                        ..default()
-                    },
-                    ..default()
-                });
-            }
-            TimelineAction::Arrow {
-                start,
-                end,
-                color: (r, g, b),
-            } => {
+    for (index, (layer, action)) in actions.iter().enumerate() {
+        match action {
+            TimelineAction::Line { start, end, color } => {
+                let mut gizmo = GizmoAsset::default();
+                gizmo.line_2d(tile_center(*start), tile_center(*end), layer.tinted(*color));
+                spawn_gizmo(&mut commands, &mut gizmo_assets, gizmo, 4.0);
+            }
+            TimelineAction::Arrow { start, end, color } => {
                 let mut gizmo = GizmoAsset::default();
-
-                let pos_start: MapPos = start.into();
-                let pos_end: MapPos = end.into();
-
-                let t_start: Transform = pos_start.into();
-                let t_end: Transform = pos_end.into();
-
                 gizmo
-                    .arrow_2d(
-                        t_start.translation.xy() + HALF_SIZE,
-                        t_end.translation.xy() + HALF_SIZE,
-                        Color::srgb_u8(r, g, b),
-                    )
+                    .arrow_2d(tile_center(*start), tile_center(*end), layer.tinted(*color))
                     .with_tip_length(SPRITE_SIZE as f32 / 2.0);
+                spawn_gizmo(&mut commands, &mut gizmo_assets, gizmo, 4.0);
+            }
+            TimelineAction::Tile { pos, color } => {
+                let Some(tile) = map.tile_mut(pos) else {
+                    skipped += 1;
+                    continue;
+                };
+                let color = layer.tinted(*color);
+                let alpha = color.alpha();
 
-                commands.spawn(Gizmo {
-                    handle: gizmo_assets.add(gizmo),
-                    line_config: GizmoLineConfig {
-                        width: 4.0,
-                        ..default()
-                    },
-                    ..default()
-                });
-            }
-            TimelineAction::Tile {
-                pos: (pos_x, pos_y),
+                tile.color = match alpha < 1. {
+                    true => tile.color.mix(&color.with_alpha(1.), alpha),
+                    false => color.lighter(0.01),
+                };
+            }
+            TimelineAction::Enqueue { pos, .. } => match map.tile_mut(pos) {
+                Some(tile) => tile.color = layer.tinted(OPEN_COLOR),
+                None => skipped += 1,
+            },
+            TimelineAction::Expand { pos } => match map.tile_mut(pos) {
+                Some(tile) => tile.color = layer.tinted(CLOSED_COLOR),
+                None => skipped += 1,
+            },
+            TimelineAction::SetParent { child, parent } => {
+                parents.insert(*child, (*parent, layer.tinted(PARENT_COLOR)));
+            }
+            TimelineAction::Found { pos } => match map.tile_mut(pos) {
+                Some(tile) => tile.color = layer.tinted(FOUND_COLOR),
+                None => skipped += 1,
+            },
+            TimelineAction::Label { pos, text } => {
+                labels.0.entry(*pos).or_default().text = Some(text.clone());
+            }
+            TimelineAction::Values { pos, values } => {
+                labels.0.entry(*pos).or_default().values = values.clone();
+            }
+            TimelineAction::Rect {
+                min,
+                max,
                 color,
+                filled,
             } => {
-                let color = Color::srgb_u8(color.0, color.1, color.2);
-                let map_pos = MapPos { x: pos_x, y: pos_y };
+                let (min, max) = (tile_center(*min), tile_center(*max));
+                let center = (min + max) / 2.;
+                let size = (max - min).abs() + Vec2::splat(SPRITE_SIZE as f32);
 
-                map.get_tile_mut(&map_pos).color = color.lighter(0.01);
+                if *filled {
+                    commands.spawn((
+                        Mesh2d(meshes.add(Rectangle::from_size(size))),
+                        MeshMaterial2d(materials.add(layer.tinted(*color))),
+                        Transform::from_translation(center.extend(shape_z(index))),
+                        WasmShape,
+                    ));
+                } else {
+                    let mut gizmo = GizmoAsset::default();
+                    gizmo.rect_2d(center, size, layer.tinted(*color));
+                    spawn_gizmo(&mut commands, &mut gizmo_assets, gizmo, 2.0);
+                }
             }
-        });
+            TimelineAction::Circle {
+                center,
+                radius,
+                color,
+                filled,
+            } => {
+                let center = tile_center(*center);
+                let radius = radius * SPRITE_SIZE as f32;
+
+                if *filled {
+                    commands.spawn((
+                        Mesh2d(meshes.add(Circle::new(radius))),
+                        MeshMaterial2d(materials.add(layer.tinted(*color))),
+                        Transform::from_translation(center.extend(shape_z(index))),
+                        WasmShape,
+                    ));
+                } else {
+                    let mut gizmo = GizmoAsset::default();
+                    gizmo
+                        .circle_2d(center, radius, layer.tinted(*color))
+                        .resolution(64);
+                    spawn_gizmo(&mut commands, &mut gizmo_assets, gizmo, 2.0);
+                }
+            }
+            TimelineAction::Polyline { points, color } => {
+                let mut gizmo = GizmoAsset::default();
+                gizmo.linestrip_2d(
+                    points.iter().copied().map(tile_center),
+                    layer.tinted(*color),
+                );
+                spawn_gizmo(&mut commands, &mut gizmo_assets, gizmo, 4.0);
+            }
+            TimelineAction::Scalar { pos, value } => {
+                if let Some(range) = &heatmap.0
+                    && !value.is_nan()
+                {
+                    match map.tile_mut(pos) {
+                        Some(tile) => tile.color = layer.tinted(colormap.color(range, *value)),
+                        None => skipped += 1,
+                    }
+                }
+            }
+            TimelineAction::ScalarField { values } if values.len() != field_len => {
+                warn!(
+                    "Scalar field has {} values, expected {}x{}",
+                    values.len(),
+                    width,
+                    height
+                );
+            }
+            TimelineAction::ScalarField { values } => {
+                if let Some(range) = &heatmap.0 {
+                    for (pos, value) in field_values(values, width) {
+                        match map.tile_mut(&pos) {
+                            Some(tile) => tile.color = layer.tinted(colormap.color(range, value)),
+                            None => skipped += 1,
+                        }
+                    }
+                }
+            }
+            TimelineAction::FlowField { directions } => {
+                flow = Some((directions, layer.tinted(FLOW_COLOR)));
+            }
+        }
+    }
+
+    if skipped > 0 {
+        warn!("Skipped {} actions outside of the map", skipped);
+    }
+
+    // All parent arrows in one gizmo, there can be one per tile
+    let mut gizmo = GizmoAsset::default();
+    for (child, (parent, color)) in parents {
+        gizmo
+            .arrow_2d(tile_center(child), tile_center(parent), color)
+            .with_tip_length(SPRITE_SIZE as f32 / 4.0);
+    }
+    spawn_gizmo(&mut commands, &mut gizmo_assets, gizmo, 2.0);
+
+    // All flow glyphs in one gizmo, centered on their tiles
+    let mut gizmo = GizmoAsset::default();
+    if let Some((directions, color)) = flow {
+        for (index, &(dx, dy)) in directions.iter().enumerate() {
+            if (dx, dy) == (0, 0) {
+                continue;
+            }
+
+            let pos = MapPos {
+                x: index as u32 % width,
+                y: index as u32 / width,
+            };
+            let half =
+                vec2(dx as f32, dy as f32).normalize() * (FLOW_LENGTH * SPRITE_SIZE as f32 / 2.);
+            gizmo
+                .arrow_2d(tile_center(pos) - half, tile_center(pos) + half, color)
+                .with_tip_length(SPRITE_SIZE as f32 / 4.0);
+        }
+    }
+    spawn_gizmo(&mut commands, &mut gizmo_assets, gizmo, 1.5);
+
+    // Crowd restarts only when the field itself changes
+    flow_field.set_if_neq(FlowField(
+        flow.map(|(directions, _)| directions.clone())
+            .unwrap_or_default(),
+    ));
+}
+
+// Timeline of another map size is cleared, a running guest is dropped with it
+fn wasm_resize(
+    mut pathfinding: ResMut<WasmPathfinding>,
+    map_size: Res<MapSize>,
+    mut layers: ResMut<Layers>,
+) {
+    if pathfinding.timeline_size == map_size.0 {
+        return;
+    }
+
+    let names = pathfinding.layer_names();
+    pathfinding.restore_timeline(Vec::new(), names, map_size.0);
+    // Redraws the map without the old timeline
+    layers.set_changed();
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Reflect, Debug, Clone, Default)]
 pub struct WasmHotReloading(pub bool);
 
+// Compilers write the file in several steps, reload waits until it is quiet
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Watches the directory of the loaded file, compilers often replace the file
+// instead of writing into it
+#[derive(Resource)]
+struct WasmWatcher {
+    file: PathBuf,
+    _watcher: RecommendedWatcher,
+    events: Mutex<Receiver<notify::Result<notify::Event>>>,
+    // Last change of the file, not reloaded yet
+    changed: Option<Instant>,
+}
+impl WasmWatcher {
+    fn new(file: PathBuf) -> notify::Result<WasmWatcher> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let dir = match file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        info!("Watching {}", file.display());
+        Ok(WasmWatcher {
+            file,
+            _watcher: watcher,
+            events: Mutex::new(events),
+            changed: None,
+        })
+    }
+
+    // Whether any received event touched the file
+    fn file_changed(&mut self) -> bool {
+        let name = self.file.file_name();
+
+        self.events
+            .get_mut()
+            .unwrap()
+            .try_iter()
+            .any(|event| match event {
+                Ok(event) => {
+                    !matches!(event.kind, EventKind::Access(_))
+                        && event.paths.iter().any(|path| path.file_name() == name)
+                }
+                Err(err) => {
+                    warn!("Watcher error: {}", err);
+                    false
+                }
+            })
+    }
+}
+
+// Follows the loaded file, watcher is dropped when hot reloading is off
+fn wasm_watch(
+    mut commands: Commands,
+    hot_reloading: Res<WasmHotReloading>,
+    pathfinding: Option<Res<WasmPathfinding>>,
+    watcher: Option<Res<WasmWatcher>>,
+) {
+    let file = match (hot_reloading.0, pathfinding) {
+        (true, Some(pathfinding)) => pathfinding.file.clone(),
+        _ => {
+            if watcher.is_some() {
+                commands.remove_resource::<WasmWatcher>();
+            }
+            return;
+        }
+    };
+    if watcher.is_some_and(|watcher| watcher.file == file) {
+        return;
+    }
+
+    match WasmWatcher::new(file.clone()) {
+        Ok(watcher) => commands.insert_resource(watcher),
+        Err(err) => {
+            error!("Can't watch {}: {}", file.display(), err);
+            commands.remove_resource::<WasmWatcher>();
+        }
+    }
+}
+
+// Broken or half written file keeps the previous module,
+// a successful reload runs the same scenario again
 fn reload_if_modified(
-    mut pathfinding: ResMut<WasmPathfinding>,
+    mut commands: Commands,
+    mut watcher: ResMut<WasmWatcher>,
+    pathfinding: Res<WasmPathfinding>,
+    mut algorithm_text: Single<&mut Text, With<SelectAlgorithmText>>,
     mut mut_state: ResMut<NextState<WasmState>>,
 ) {
-    let metadata = fs::metadata(pathfinding.file.clone()).unwrap();
-    let modified = metadata.modified().unwrap();
-    let age = SystemTime::now().duration_since(modified).unwrap();
+    let now = Instant::now();
+    if watcher.file_changed() {
+        watcher.changed = Some(now);
+    }
+    match watcher.changed {
+        Some(changed) if now - changed >= RELOAD_DEBOUNCE => watcher.changed = None,
+        _ => return,
+    }
 
-    if age < Duration::from_secs(1) {
-        info!("Reloading wasm...");
-        *pathfinding = WasmPathfinding::load(&pathfinding.file).unwrap();
-        mut_state.set(WasmState::Run);
+    info!("Reloading wasm...");
+    match WasmPathfinding::load(&watcher.file) {
+        Ok(wasm) => {
+            algorithm_text.0 = wasm.name();
+            commands.insert_resource(wasm);
+            mut_state.set(WasmState::Run);
+        }
+        Err(err) => {
+            error!("Reload failed: {}", err);
+            algorithm_text.0 = format!(
+                "{}\nReload failed, previous version is used: {err}",
+                pathfinding.name()
+            );
+        }
     }
 }
 
@@ -217,21 +910,43 @@ pub struct WasmRunnerPlugin;
 impl Plugin for WasmRunnerPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<WasmState>()
+            .init_resource::<Movement>()
+            .init_resource::<Statistics>()
+            .init_resource::<WasmStreaming>()
+            .add_systems(OnEnter(WasmState::Run), wasm_run)
+            // Timeline is redrawn after every run, every streamed batch,
+            // every layer change and colormap change
             .add_systems(
-                OnEnter(WasmState::Run),
-                (wasm_clean, wasm_run, show_wasm_actions).chain(),
+                Update,
+                (
+                    wasm_resize.run_if(
+                        |size: Res<MapSize>, pathfinding: Option<Res<WasmPathfinding>>| {
+                            size.is_changed() && pathfinding.is_some()
+                        },
+                    ),
+                    wasm_stream.run_if(
+                        in_state(WasmState::Streaming).and(resource_exists::<WasmPathfinding>),
+                    ),
+                    (wasm_clean, show_wasm_actions).chain().run_if(
+                        |layers: Res<Layers>,
+                         colormap: Res<Colormap>,
+                         pathfinding: Option<Res<WasmPathfinding>>| {
+                            (layers.is_changed() || colormap.is_changed()) && pathfinding.is_some()
+                        },
+                    ),
+                )
+                    .chain(),
             )
             .init_resource::<WasmHotReloading>()
             .add_systems(
                 Update,
-                reload_if_modified
-                    .run_if(
-                        |hot_reloading: Res<WasmHotReloading>,
-                         pathfinding: Option<Res<WasmPathfinding>>| {
-                            hot_reloading.0 && pathfinding.is_some()
-                        },
-                    )
-                    .run_if(on_timer(Duration::from_secs(1))),
+                (
+                    wasm_watch,
+                    reload_if_modified.run_if(
+                        resource_exists::<WasmWatcher>.and(resource_exists::<WasmPathfinding>),
+                    ),
+                )
+                    .chain(),
             );
     }
 }
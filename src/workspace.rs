@@ -0,0 +1,296 @@
+use std::{any::TypeId, fs, path::PathBuf};
+
+use bevy::{
+    prelude::*,
+    reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer},
+    scene::ron::{self, ser::PrettyConfig},
+};
+use rfd::FileDialog;
+use serde::de::DeserializeSeed;
+
+use crate::{
+    api::TimelineEntry,
+    crowd::Crowd,
+    debugger::Breakpoints,
+    goals::{Flag, Fox},
+    heatmap::Colormap,
+    labels::LabelMode,
+    layers::{Layers, TINTS},
+    map::{self, MAX_MAP_SIZE, MIN_MAP_SIZE, Map, MapPos},
+    map_image::MapImageSettings,
+    movement::Movement,
+    reachability::ReachabilityOverlay,
+    reference::ReferenceSettings,
+    statistics::Statistics,
+    ui::SelectAlgorithmText,
+    wasm::{WasmHotReloading, WasmPathfinding, WasmStreaming},
+};
+
+const EXTENSION: &str = "ron";
+
+// Everything needed to reopen the session as it was
+#[derive(Reflect, Debug, Clone, Default)]
+pub struct Workspace {
+    pub map: Map,
+    pub start: MapPos,
+    pub end: MapPos,
+    pub movement: Movement,
+    pub camera: Transform,
+    pub algorithm: Option<String>,
+    // Last run, shown again without running the guest
+    pub timeline: Vec<TimelineEntry>,
+    pub timeline_layers: Vec<String>,
+    pub statistics: Statistics,
+    // Overlay settings
+    pub layers: Layers,
+    pub label_mode: LabelMode,
+    pub colormap: Colormap,
+    pub crowd: Crowd,
+    // Files saved before these settings were added get defaults
+    #[reflect(default)]
+    pub areas: ReachabilityOverlay,
+    #[reflect(default)]
+    pub reference: ReferenceSettings,
+    #[reflect(default)]
+    pub streaming: WasmStreaming,
+    #[reflect(default)]
+    pub breakpoints: Breakpoints,
+    #[reflect(default)]
+    pub hot_reloading: WasmHotReloading,
+    #[reflect(default)]
+    pub map_image: MapImageSettings,
+}
+impl Workspace {
+    pub fn to_ron(&self, registry: &AppTypeRegistry) -> Result<String, String> {
+        let registry = registry.read();
+        let serializer = TypedReflectSerializer::new(self, &registry);
+
+        ron::ser::to_string_pretty(&serializer, PrettyConfig::default())
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn from_ron(text: &str, registry: &AppTypeRegistry) -> Result<Workspace, String> {
+        let registry = registry.read();
+        let registration = registry
+            .get(TypeId::of::<Workspace>())
+            .ok_or("Workspace type is not registered")?;
+
+        let mut deserializer = ron::Deserializer::from_str(text).map_err(|err| err.to_string())?;
+        let reflected = TypedReflectDeserializer::new(registration, &registry)
+            .deserialize(&mut deserializer)
+            .map_err(|err| err.to_string())?;
+
+        let workspace = Workspace::from_reflect(&*reflected)
+            .ok_or_else(|| "Workspace file doesn't match current format".to_owned())?;
+        workspace.validate()?;
+        Ok(workspace)
+    }
+
+    // Files can be edited by hand, everything used as a map index is checked
+    fn validate(&self) -> Result<(), String> {
+        let (width, height) = (self.map.width(), self.map.height());
+        if !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&width)
+            || !(MIN_MAP_SIZE..=MAX_MAP_SIZE).contains(&height)
+        {
+            return Err(format!(
+                "Map is {width}x{height}, sides must be {MIN_MAP_SIZE}..={MAX_MAP_SIZE} tiles"
+            ));
+        }
+        if let Some(y) = self
+            .map
+            .0
+            .iter()
+            .position(|row| row.len() != width as usize)
+        {
+            return Err(format!("Map row {y} is not {width} tiles wide"));
+        }
+
+        let outside = |pos: &MapPos| pos.x >= width || pos.y >= height;
+        if let Some(pos) = [self.start, self.end].iter().find(|pos| outside(pos)) {
+            return Err(format!("Goal ({}, {}) is outside of the map", pos.x, pos.y));
+        }
+        if let Some(pos) = self.breakpoints.tile.filter(outside) {
+            return Err(format!(
+                "Breakpoint ({}, {}) is outside of the map",
+                pos.x, pos.y
+            ));
+        }
+
+        if let Some(layer) = self.layers.0.iter().find(|layer| layer.tint >= TINTS.len()) {
+            return Err(format!(
+                "Layer {} has unknown tint {}",
+                layer.name, layer.tint
+            ));
+        }
+
+        for entry in &self.timeline {
+            if entry.layer >= self.timeline_layers.len() {
+                return Err(format!("Timeline layer {} doesn't exist", entry.layer));
+            }
+            if let Some(pos) = entry.action.tile().filter(outside) {
+                return Err(format!(
+                    "Timeline action at ({}, {}) is outside of the map",
+                    pos.x, pos.y
+                ));
+            }
+            if let Some(len) = entry
+                .action
+                .field_len()
+                .filter(|len| *len != (width * height) as usize)
+            {
+                return Err(format!(
+                    "Timeline field has {len} values, expected {width}x{height}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn pick_file() -> FileDialog {
+    FileDialog::new()
+        .add_filter("Workspace", &[EXTENSION])
+        .set_directory(std::env::current_dir().unwrap_or_default())
+}
+
+pub fn workspace_save(
+    registry: Res<AppTypeRegistry>,
+    map: Res<Map>,
+    fox: Single<&MapPos, With<Fox>>,
+    flag: Single<&MapPos, With<Flag>>,
+    movement: Res<Movement>,
+    camera: Single<&Transform, With<Camera>>,
+    pathfinding: Option<Res<WasmPathfinding>>,
+    statistics: Res<Statistics>,
+    layers: Res<Layers>,
+    label_mode: Res<LabelMode>,
+    colormap: Res<Colormap>,
+    crowd: Res<Crowd>,
+    (areas, reference, streaming, breakpoints, hot_reloading, map_image): (
+        Res<ReachabilityOverlay>,
+        Res<ReferenceSettings>,
+        Res<WasmStreaming>,
+        Res<Breakpoints>,
+        Res<WasmHotReloading>,
+        Res<MapImageSettings>,
+    ),
+) {
+    let Some(file) = pick_file().set_file_name("workspace.ron").save_file() else {
+        return;
+    };
+
+    let (timeline, timeline_layers) = pathfinding
+        .as_ref()
+        .map(|pathfinding| pathfinding.timeline())
+        .unwrap_or_default();
+
+    // Tile colors come from the timeline, it is replayed on open
+    let mut map = map.clone();
+    map.0
+        .iter_mut()
+        .flatten()
+        .for_each(|tile| tile.color = Color::WHITE);
+
+    let workspace = Workspace {
+        map,
+        start: **fox,
+        end: **flag,
+        movement: *movement,
+        camera: **camera,
+        algorithm: pathfinding.map(|wasm| wasm.file().to_string_lossy().into_owned()),
+        timeline,
+        timeline_layers,
+        statistics: statistics.clone(),
+        layers: layers.clone(),
+        label_mode: *label_mode,
+        colormap: *colormap,
+        crowd: *crowd,
+        areas: areas.clone(),
+        reference: reference.clone(),
+        // Opened workspace doesn't start paused
+        streaming: WasmStreaming {
+            paused: false,
+            ..streaming.clone()
+        },
+        breakpoints: *breakpoints,
+        hot_reloading: hot_reloading.clone(),
+        map_image: *map_image,
+    };
+
+    match workspace
+        .to_ron(&registry)
+        .and_then(|text| fs::write(&file, text).map_err(|err| err.to_string()))
+    {
+        Ok(()) => info!("Saved workspace to {}", file.display()),
+        Err(err) => error!("Can't save workspace: {}", err),
+    }
+}
+
+pub fn workspace_open(
+    mut commands: Commands,
+    registry: Res<AppTypeRegistry>,
+    fox: Single<Entity, With<Fox>>,
+    flag: Single<Entity, With<Flag>>,
+    mut camera: Single<&mut Transform, With<Camera>>,
+    mut algorithm_text: Single<&mut Text, With<SelectAlgorithmText>>,
+) {
+    let Some(file) = pick_file().pick_file() else {
+        return;
+    };
+
+    let workspace = match fs::read_to_string(&file)
+        .map_err(|err| err.to_string())
+        .and_then(|text| Workspace::from_ron(&text, &registry))
+    {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            error!("Can't open workspace: {}", err);
+            return;
+        }
+    };
+
+    let map_size = uvec2(workspace.map.width(), workspace.map.height());
+    map::load_map(&mut commands, workspace.map);
+    commands.entity(*fox).insert(workspace.start);
+    commands.entity(*flag).insert(workspace.end);
+    **camera = workspace.camera;
+
+    commands.insert_resource(workspace.movement);
+    commands.insert_resource(workspace.label_mode);
+    commands.insert_resource(workspace.colormap);
+    commands.insert_resource(workspace.crowd);
+    commands.insert_resource(workspace.areas);
+    commands.insert_resource(workspace.reference);
+    commands.insert_resource(workspace.streaming);
+    commands.insert_resource(workspace.breakpoints);
+    commands.insert_resource(workspace.hot_reloading);
+    commands.insert_resource(workspace.map_image);
+    commands.insert_resource(workspace.statistics);
+    // Triggers drawing of the restored timeline
+    commands.insert_resource(workspace.layers);
+
+    let Some(algorithm) = workspace.algorithm else {
+        commands.remove_resource::<WasmPathfinding>();
+        algorithm_text.0 = "...".to_owned();
+        return;
+    };
+    match WasmPathfinding::load(&PathBuf::from(algorithm)) {
+        Ok(mut wasm) => {
+            wasm.restore_timeline(workspace.timeline, workspace.timeline_layers, map_size);
+            algorithm_text.0 = wasm.name();
+            commands.insert_resource(wasm);
+        }
+        Err(err) => {
+            error!("{}", err);
+            algorithm_text.0 = format!("Error loading wasm: {err}");
+            commands.remove_resource::<WasmPathfinding>();
+        }
+    }
+}
+
+pub struct WorkspacePlugin;
+impl Plugin for WorkspacePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Workspace>();
+    }
+}